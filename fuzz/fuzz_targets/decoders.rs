@@ -0,0 +1,29 @@
+//! Feeds arbitrary bytes to every pool decoder. A decoder may reject the input but must not panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solana_onchain_arbitrage_bot::dex::heaven::HeavenPoolState;
+use solana_onchain_arbitrage_bot::dex::meteora::damm_info::load_damm_pool;
+use solana_onchain_arbitrage_bot::dex::meteora::dammv2_info::MeteoraDAmmV2Info;
+use solana_onchain_arbitrage_bot::dex::meteora::dlmm_info::DlmmInfo;
+use solana_onchain_arbitrage_bot::dex::pump::PumpAmmInfo;
+use solana_onchain_arbitrage_bot::dex::raydium::{PoolState, RaydiumAmmInfo, RaydiumCpAmmInfo};
+use solana_onchain_arbitrage_bot::dex::solfi::info::SolfiInfo;
+use solana_onchain_arbitrage_bot::dex::vertigo::VertigoInfo;
+use solana_onchain_arbitrage_bot::dex::whirlpool::state::Whirlpool;
+use solana_program::pubkey::Pubkey;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = RaydiumAmmInfo::load_checked(data);
+    let _ = RaydiumCpAmmInfo::load_checked(data);
+    let _ = PoolState::load_checked(data);
+    let _ = PumpAmmInfo::load_checked(data);
+    let _ = DlmmInfo::load_checked(data);
+    let _ = Whirlpool::try_deserialize(data);
+    let _ = load_damm_pool(data);
+    let _ = SolfiInfo::load_checked(data);
+    let _ = MeteoraDAmmV2Info::load_checked(data);
+    let _ = VertigoInfo::load_checked(data, &Pubkey::default());
+    let _ = HeavenPoolState::parse(data);
+});
@@ -0,0 +1,358 @@
+use crate::config::{Config, MintConfig};
+use crate::constants::sol_mint;
+use crate::pools::PoolRef;
+use crate::quote::{constant_product_fee_bps, constant_product_out};
+use crate::refresh::{initialize_pool_data, token_account_amount};
+use crate::rpc::AccountSource;
+use crate::snapshot::{read_snapshots, AccountSnapshot};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+/// Fee of the one signature an arbitrage transaction carries.
+const SIGNATURE_FEE: u64 = 5_000;
+
+/// Accounts as of the snapshots applied so far.
+#[derive(Default)]
+pub struct SnapshotAccounts {
+    accounts: Mutex<HashMap<Pubkey, Account>>,
+}
+
+impl SnapshotAccounts {
+    pub fn apply(&self, snapshot: &AccountSnapshot) -> anyhow::Result<()> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .insert(snapshot.pubkey()?, snapshot.account()?);
+        Ok(())
+    }
+}
+
+impl AccountSource for SnapshotAccounts {
+    fn get_account(&self, pubkey: &Pubkey) -> anyhow::Result<Account> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .get(pubkey)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Account {} is not in the snapshots", pubkey))
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        let accounts = self.accounts.lock().unwrap();
+        Ok(pubkeys.iter().map(|p| accounts.get(p).cloned()).collect())
+    }
+}
+
+/// A set of pools traded together, with the profit it must clear. Mirrors the routes of a mint,
+/// or every pool with no minimum when the mint has no routes.
+struct Target {
+    name: String,
+    pools: Vec<PoolRef>,
+    minimum_profit: u64,
+}
+
+/// A buy on one pool and a sell on another, sized for the largest quoted profit.
+#[derive(Debug, Clone)]
+pub struct Arbitrage {
+    pub buy_pool: Pubkey,
+    pub sell_pool: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+impl Arbitrage {
+    pub fn profit(&self) -> i64 {
+        self.amount_out as i64 - self.amount_in as i64
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TargetReport {
+    pub name: String,
+    /// Slots at which a vault of the target changed.
+    pub steps: usize,
+    /// Steps whose best arbitrage cleared the minimum profit plus fees.
+    pub opportunities: usize,
+    /// Quoted profit of the opportunities, before fees.
+    pub expected_profit: i64,
+    /// Fees of one transaction per opportunity.
+    pub fees: u64,
+    pub best: Option<(u64, Arbitrage)>,
+}
+
+#[derive(Debug)]
+pub struct BacktestReport {
+    pub mint: String,
+    pub first_slot: u64,
+    pub last_slot: u64,
+    /// Pools that were left out because they can't be quoted from vault balances.
+    pub unquoted_pools: Vec<Pubkey>,
+    pub fee_per_transaction: u64,
+    pub targets: Vec<TargetReport>,
+}
+
+/// Fee of one arbitrage transaction at `compute_unit_price` micro-lamports per compute unit.
+pub fn transaction_fee(compute_unit_limit: u32, compute_unit_price: u64) -> u64 {
+    let priority_fee =
+        (compute_unit_limit as u128 * compute_unit_price as u128).div_ceil(1_000_000);
+    SIGNATURE_FEE + priority_fee as u64
+}
+
+/// Replays `snapshots` for the pools of `mint_config`. Pools are loaded from the first recorded
+/// state of every account, then the best two-pool arbitrage of each target is quoted at every
+/// slot where one of its vaults changed.
+pub async fn run_backtest(
+    mint_config: &MintConfig,
+    snapshots: &[AccountSnapshot],
+    compute_unit_limit: u32,
+    compute_unit_price: u64,
+) -> anyhow::Result<BacktestReport> {
+    let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) else {
+        anyhow::bail!("No snapshots to replay");
+    };
+
+    // Pools are loaded once, from the first state of each account
+    let initial = Arc::new(SnapshotAccounts::default());
+    let mut seen = HashSet::new();
+    for snapshot in snapshots {
+        if seen.insert(snapshot.address.as_str()) {
+            initial.apply(snapshot)?;
+        }
+    }
+    let pool_data = initialize_pool_data(
+        &mint_config.mint,
+        &Pubkey::default().to_string(),
+        mint_config.raydium_pool_list.as_ref(),
+        mint_config.raydium_cp_pool_list.as_ref(),
+        mint_config.pump_pool_list.as_ref(),
+        mint_config.meteora_dlmm_pool_list.as_ref(),
+        mint_config.whirlpool_pool_list.as_ref(),
+        mint_config.raydium_clmm_pool_list.as_ref(),
+        mint_config.meteora_damm_pool_list.as_ref(),
+        mint_config.solfi_pool_list.as_ref(),
+        mint_config.meteora_damm_v2_pool_list.as_ref(),
+        mint_config.vertigo_pool_list.as_ref(),
+        mint_config.heaven_pool_list.as_ref(),
+        initial,
+    )
+    .await?;
+
+    // The executor starts and ends in SOL, so only SOL pools are quoted
+    let (quotable, unquoted): (Vec<PoolRef>, Vec<PoolRef>) = pool_data
+        .pools()
+        .into_iter()
+        .partition(|p| constant_product_fee_bps(p.dex).is_some() && p.base_mint == sol_mint());
+    let targets = targets(mint_config, &quotable)?;
+
+    let fee_per_transaction = transaction_fee(compute_unit_limit, compute_unit_price);
+    let mut reports: Vec<TargetReport> = targets
+        .iter()
+        .map(|t| TargetReport {
+            name: t.name.clone(),
+            ..Default::default()
+        })
+        .collect();
+
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    for slot_snapshots in snapshots.chunk_by(|a, b| a.slot == b.slot) {
+        let slot = slot_snapshots[0].slot;
+        let mut changed = HashSet::new();
+        for snapshot in slot_snapshots {
+            let address = snapshot.pubkey()?;
+            if let Some(amount) = token_account_amount(&snapshot.account()?) {
+                if balances.insert(address, amount) != Some(amount) {
+                    changed.insert(address);
+                }
+            }
+        }
+
+        for (target, report) in targets.iter().zip(reports.iter_mut()) {
+            let touched = target
+                .pools
+                .iter()
+                .any(|p| changed.contains(&p.token_vault) || changed.contains(&p.base_vault));
+            if !touched {
+                continue;
+            }
+            report.steps += 1;
+
+            let Some(arbitrage) = best_arbitrage(&target.pools, &balances) else {
+                continue;
+            };
+            if arbitrage.profit() < (target.minimum_profit + fee_per_transaction) as i64 {
+                continue;
+            }
+            report.opportunities += 1;
+            report.expected_profit += arbitrage.profit();
+            report.fees += fee_per_transaction;
+            if report
+                .best
+                .as_ref()
+                .is_none_or(|(_, best)| arbitrage.profit() > best.profit())
+            {
+                report.best = Some((slot, arbitrage));
+            }
+        }
+    }
+
+    Ok(BacktestReport {
+        mint: mint_config.mint.clone(),
+        first_slot: first.slot,
+        last_slot: last.slot,
+        unquoted_pools: unquoted.iter().map(|p| p.pool).collect(),
+        fee_per_transaction,
+        targets: reports,
+    })
+}
+
+fn targets(mint_config: &MintConfig, quotable: &[PoolRef]) -> anyhow::Result<Vec<Target>> {
+    let Some(routes) = &mint_config.routes else {
+        return Ok(vec![Target {
+            name: "all pools".to_string(),
+            pools: quotable.to_vec(),
+            minimum_profit: 0,
+        }]);
+    };
+    routes
+        .iter()
+        .map(|route| {
+            let pools: HashSet<Pubkey> = route
+                .pools
+                .iter()
+                .map(|p| Pubkey::from_str(p))
+                .collect::<Result<_, _>>()
+                .map_err(|e| anyhow::anyhow!("Invalid pool in route {}: {}", route.name, e))?;
+            Ok(Target {
+                name: route.name.clone(),
+                pools: quotable
+                    .iter()
+                    .filter(|p| pools.contains(&p.pool))
+                    .cloned()
+                    .collect(),
+                minimum_profit: route.minimum_profit,
+            })
+        })
+        .collect()
+}
+
+/// The most profitable SOL -> token -> SOL trade through two of `pools`.
+pub fn best_arbitrage(pools: &[PoolRef], balances: &HashMap<Pubkey, u64>) -> Option<Arbitrage> {
+    let reserves = |pool: &PoolRef| {
+        let token = *balances.get(&pool.token_vault)?;
+        let base = *balances.get(&pool.base_vault)?;
+        Some((token, base, constant_product_fee_bps(pool.dex)?))
+    };
+
+    let mut best: Option<Arbitrage> = None;
+    for buy in pools {
+        let Some((buy_token, buy_base, buy_fee)) = reserves(buy) else {
+            continue;
+        };
+        for sell in pools.iter().filter(|p| p.pool != buy.pool) {
+            let Some((sell_token, sell_base, sell_fee)) = reserves(sell) else {
+                continue;
+            };
+            let quote = |amount_in: u64| {
+                let tokens = constant_product_out(amount_in, buy_base, buy_token, buy_fee);
+                constant_product_out(tokens, sell_token, sell_base, sell_fee)
+            };
+            let amount_in = optimal_input(buy_base, |x| quote(x) as i128 - x as i128);
+            let arbitrage = Arbitrage {
+                buy_pool: buy.pool,
+                sell_pool: sell.pool,
+                amount_in,
+                amount_out: quote(amount_in),
+            };
+            if arbitrage.profit() > best.as_ref().map_or(0, Arbitrage::profit) {
+                best = Some(arbitrage);
+            }
+        }
+    }
+    best
+}
+
+/// Input in `[0, max]` that maximizes `profit`, which is concave for constant product pools.
+fn optimal_input(max: u64, profit: impl Fn(u64) -> i128) -> u64 {
+    let (mut lo, mut hi) = (0u64, max);
+    while hi - lo > 2 {
+        let m1 = lo + (hi - lo) / 3;
+        let m2 = hi - (hi - lo) / 3;
+        if profit(m1) < profit(m2) {
+            lo = m1;
+        } else {
+            hi = m2;
+        }
+    }
+    (lo..=hi).max_by_key(|x| profit(*x)).unwrap_or(0)
+}
+
+/// Backs the `backtest` command: replays `snapshots` for every mint of the config, or only `mint`.
+pub async fn run_backtest_command(
+    config: &Config,
+    snapshots: &Path,
+    mint: Option<&str>,
+    compute_unit_price: Option<u64>,
+) -> anyhow::Result<()> {
+    let compute_unit_price = compute_unit_price
+        .or_else(|| config.spam.as_ref().map(|s| s.compute_unit_price))
+        .unwrap_or(1000);
+    let snapshots = read_snapshots(snapshots)?;
+    info!("Loaded {} account snapshots", snapshots.len());
+
+    let mint_configs: Vec<&MintConfig> = config
+        .routing
+        .mint_config_list
+        .iter()
+        .filter(|m| mint.is_none_or(|mint| m.mint == mint))
+        .collect();
+    if mint_configs.is_empty() {
+        anyhow::bail!("No mint of the config matches {}", mint.unwrap_or_default());
+    }
+
+    for mint_config in mint_configs {
+        let report = run_backtest(
+            mint_config,
+            &snapshots,
+            config.bot.compute_unit_limit,
+            compute_unit_price,
+        )
+        .await?;
+        info!(
+            "Backtest of mint {} over slots {}..={}, {} lamports fee per transaction at {} micro-lamports per CU",
+            report.mint,
+            report.first_slot,
+            report.last_slot,
+            report.fee_per_transaction,
+            compute_unit_price
+        );
+        if !report.unquoted_pools.is_empty() {
+            info!(
+                "    Left out {} pools that can't be quoted from vault balances",
+                report.unquoted_pools.len()
+            );
+        }
+        for target in &report.targets {
+            info!(
+                "    {}: {} opportunities in {} steps, expected profit {} lamports, fees {} lamports, net {} lamports",
+                target.name,
+                target.opportunities,
+                target.steps,
+                target.expected_profit,
+                target.fees,
+                target.expected_profit - target.fees as i64
+            );
+            if let Some((slot, best)) = &target.best {
+                info!(
+                    "        Best at slot {}: {} -> {} lamports buying on {} and selling on {}",
+                    slot, best.amount_in, best.amount_out, best.buy_pool, best.sell_pool
+                );
+            }
+        }
+    }
+    Ok(())
+}
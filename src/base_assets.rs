@@ -0,0 +1,119 @@
+use crate::config::{BaseAssetConfig, Config};
+use crate::constants::sol_mint;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+/// Where flashloans of a base asset are borrowed from.
+#[derive(Debug, Clone)]
+pub enum FlashloanVault {
+    /// The executor vaults, picked at random per transaction.
+    Executor,
+    /// A fixed vault token account and the authority that owns it.
+    Fixed {
+        authority: Pubkey,
+        token_account: Pubkey,
+    },
+}
+
+/// A base other than SOL that the executor can start and end a swap in.
+/// The executor bridges it to SOL through a Raydium V4 SOL pool.
+#[derive(Debug, Clone)]
+pub struct BaseAsset {
+    pub mint: Pubkey,
+    pub bridge_pool: Pubkey,
+    /// Vault of `mint` in the bridge pool.
+    pub bridge_vault: Pubkey,
+    /// SOL vault of the bridge pool.
+    pub bridge_sol_vault: Pubkey,
+    pub flashloan_vault: Option<FlashloanVault>,
+}
+
+/// The bases the executor can trade: SOL plus every entry of the table.
+#[derive(Debug, Clone)]
+pub struct BaseAssets {
+    assets: Vec<BaseAsset>,
+}
+
+impl BaseAssets {
+    /// USDC and USD1, with the `base_assets` entries of `config` added. An entry for a default mint replaces it.
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let mut assets = default_base_assets();
+        for config in config.base_assets.iter().flatten() {
+            let asset = parse_base_asset(config)?;
+            if asset.mint == sol_mint() {
+                anyhow::bail!("SOL is always a base asset and can't be configured");
+            }
+            match assets.iter_mut().find(|a| a.mint == asset.mint) {
+                Some(existing) => *existing = asset,
+                None => assets.push(asset),
+            }
+        }
+        Ok(Self { assets })
+    }
+
+    pub fn get(&self, mint: &Pubkey) -> Option<&BaseAsset> {
+        self.assets.iter().find(|a| a.mint == *mint)
+    }
+
+    /// The first entry, in table order, that one of `mints` uses.
+    pub fn bridged<'a>(&self, mints: impl IntoIterator<Item = &'a Pubkey>) -> Option<&BaseAsset> {
+        let mints: Vec<&Pubkey> = mints.into_iter().collect();
+        self.assets.iter().find(|a| mints.contains(&&a.mint))
+    }
+
+    /// Whether a flashloan can borrow `mint`.
+    pub fn can_flashloan(&self, mint: &Pubkey) -> bool {
+        *mint == sol_mint() || self.get(mint).is_some_and(|a| a.flashloan_vault.is_some())
+    }
+}
+
+fn default_base_assets() -> Vec<BaseAsset> {
+    vec![
+        // USDC through the Raydium SOL/USDC pool
+        BaseAsset {
+            mint: Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap(),
+            bridge_pool: Pubkey::from_str("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2").unwrap(),
+            bridge_vault: Pubkey::from_str("HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz").unwrap(),
+            bridge_sol_vault: Pubkey::from_str("DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz")
+                .unwrap(),
+            flashloan_vault: Some(FlashloanVault::Executor),
+        },
+        // USD1 through the Raydium SOL/USD1 pool
+        BaseAsset {
+            mint: Pubkey::from_str("USD1ttGY1N17NEEHLmELoaybftRBUSErhqYiQzvEmuB").unwrap(),
+            bridge_pool: Pubkey::from_str("FaDoeere161VKUFqcrQEM8it6kSCHKrLyq7wWyPvBkPq").unwrap(),
+            bridge_vault: Pubkey::from_str("GLx7TdT66CPKYJBn3Pzc9khrfXEx6mXtAiE8uskGBQJq").unwrap(),
+            bridge_sol_vault: Pubkey::from_str("3U9HB8KNHXmAmiGMbDsj6fBxzM63dfX5JbaYs5oTHbtu")
+                .unwrap(),
+            flashloan_vault: None,
+        },
+    ]
+}
+
+fn parse_base_asset(config: &BaseAssetConfig) -> anyhow::Result<BaseAsset> {
+    let parse = |field: &str, value: &str| {
+        Pubkey::from_str(value).map_err(|e| {
+            anyhow::anyhow!(
+                "Invalid {} {} of base asset {}: {}",
+                field,
+                value,
+                config.mint,
+                e
+            )
+        })
+    };
+    let flashloan_vault = match &config.flashloan_vault {
+        Some(vault) => Some(FlashloanVault::Fixed {
+            authority: parse("flashloan_vault.authority", &vault.authority)?,
+            token_account: parse("flashloan_vault.token_account", &vault.token_account)?,
+        }),
+        None => None,
+    };
+    Ok(BaseAsset {
+        mint: parse("mint", &config.mint)?,
+        bridge_pool: parse("bridge_pool", &config.bridge_pool)?,
+        bridge_vault: parse("bridge_vault", &config.bridge_vault)?,
+        bridge_sol_vault: parse("bridge_sol_vault", &config.bridge_sol_vault)?,
+        flashloan_vault,
+    })
+}
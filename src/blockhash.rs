@@ -0,0 +1,207 @@
+use crate::config::BlockhashConfig;
+use crate::rpc_pool::RpcPool;
+use crate::supervisor::Shutdown;
+use serde_json::json;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_response::{Response, RpcBlockhash};
+use solana_sdk::hash::Hash;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, warn};
+
+const DEFAULT_REFRESH_SLOTS: u64 = 10;
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(400);
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// A blockhash together with what is needed to tell how long it stays usable.
+#[derive(Debug, Clone, Copy)]
+pub struct CachedBlockhash {
+    pub hash: Hash,
+    /// Last block height at which transactions using `hash` are processed.
+    pub last_valid_block_height: u64,
+    /// Slot the RPC answered at.
+    pub slot: u64,
+    pub fetched_at: Instant,
+}
+
+impl CachedBlockhash {
+    pub fn age(&self) -> Duration {
+        self.fetched_at.elapsed()
+    }
+}
+
+/// Keeps a recent blockhash. It is refreshed once the chain has moved `refresh_slots` past it,
+/// asking the read endpoints healthiest first and then the fallbacks until one answers, and is
+/// not handed out once older than `max_age`.
+pub struct BlockhashService {
+    rpc_pool: Arc<RpcPool>,
+    fallback_clients: Vec<Arc<RpcClient>>,
+    refresh_slots: u64,
+    max_age: Duration,
+    poll_interval: Duration,
+    metrics_path: Option<PathBuf>,
+    current: Mutex<Option<CachedBlockhash>>,
+    /// Latest slot seen by `poll`.
+    latest_slot: Mutex<u64>,
+}
+
+impl BlockhashService {
+    pub fn new(
+        rpc_pool: Arc<RpcPool>,
+        fallback_clients: Vec<Arc<RpcClient>>,
+        config: Option<&BlockhashConfig>,
+    ) -> Self {
+        Self {
+            rpc_pool,
+            fallback_clients,
+            refresh_slots: config
+                .and_then(|c| c.refresh_slots)
+                .unwrap_or(DEFAULT_REFRESH_SLOTS),
+            max_age: config
+                .and_then(|c| c.max_age)
+                .map_or(DEFAULT_MAX_AGE, Duration::from_millis),
+            poll_interval: config
+                .and_then(|c| c.poll_interval)
+                .map_or(DEFAULT_POLL_INTERVAL, Duration::from_millis),
+            metrics_path: config
+                .and_then(|c| c.metrics_path.as_ref())
+                .map(PathBuf::from),
+            current: Mutex::new(None),
+            latest_slot: Mutex::new(0),
+        }
+    }
+
+    /// The cached blockhash, unless it is older than the configured maximum age.
+    pub fn fresh(&self) -> anyhow::Result<Hash> {
+        let current = *self.current.lock().unwrap_or_else(|e| e.into_inner());
+        match current {
+            Some(blockhash) if blockhash.age() <= self.max_age => Ok(blockhash.hash),
+            Some(blockhash) => anyhow::bail!(
+                "Blockhash {} is {}ms old, over the limit of {}ms",
+                blockhash.hash,
+                blockhash.age().as_millis(),
+                self.max_age.as_millis()
+            ),
+            None => anyhow::bail!("No blockhash fetched yet"),
+        }
+    }
+
+    pub fn current(&self) -> Option<CachedBlockhash> {
+        *self.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drops the cached blockhash if it is `hash`, so the next poll fetches a new one.
+    pub fn invalidate(&self, hash: &Hash) {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_some_and(|blockhash| blockhash.hash == *hash) {
+            warn!("Blockhash {} not found by an RPC, fetching a new one", hash);
+            *current = None;
+        }
+    }
+
+    /// Fetches a new blockhash from the first RPC that answers.
+    pub fn refresh(&self) -> anyhow::Result<CachedBlockhash> {
+        let blockhash = self.with_fallback("blockhash", fetch_blockhash)?;
+        *self.current.lock().unwrap_or_else(|e| e.into_inner()) = Some(blockhash);
+        debug!(
+            "Blockhash refreshed: {} at slot {}, valid until block height {}",
+            blockhash.hash, blockhash.slot, blockhash.last_valid_block_height
+        );
+        Ok(blockhash)
+    }
+
+    /// Reads the current slot and refreshes the blockhash once the chain has moved far enough.
+    pub fn poll(&self) -> anyhow::Result<()> {
+        let slot = self.with_fallback("slot", |rpc_client| Ok(rpc_client.get_slot()?))?;
+        *self.latest_slot.lock().unwrap_or_else(|e| e.into_inner()) = slot;
+        let stale = self
+            .current()
+            .is_none_or(|blockhash| slot >= blockhash.slot + self.refresh_slots);
+        if stale {
+            self.refresh()?;
+        }
+        Ok(())
+    }
+
+    fn with_fallback<T>(
+        &self,
+        what: &str,
+        request: impl Fn(&RpcClient) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let mut last_error = None;
+        let rpc_clients = self.rpc_pool.clients_by_health();
+        for (i, rpc_client) in rpc_clients.iter().chain(&self.fallback_clients).enumerate() {
+            match request(rpc_client) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    warn!("Failed to fetch {} from RPC {}: {:?}", what, i, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No RPC to fetch {} from", what)))
+    }
+
+    /// Blockhash age and slot lag in the Prometheus text format.
+    pub fn metrics(&self) -> String {
+        let latest_slot = *self.latest_slot.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(blockhash) = self.current() else {
+            return String::new();
+        };
+        format!(
+            "# HELP blockhash_age_seconds Seconds since the cached blockhash was fetched\n\
+             # TYPE blockhash_age_seconds gauge\n\
+             blockhash_age_seconds {:.3}\n\
+             # HELP blockhash_slots_behind Slots the chain has moved past the cached blockhash\n\
+             # TYPE blockhash_slots_behind gauge\n\
+             blockhash_slots_behind {}\n\
+             # HELP blockhash_last_valid_block_height Last block height the cached blockhash is valid at\n\
+             # TYPE blockhash_last_valid_block_height gauge\n\
+             blockhash_last_valid_block_height {}\n",
+            blockhash.age().as_secs_f64(),
+            latest_slot.saturating_sub(blockhash.slot),
+            blockhash.last_valid_block_height
+        )
+    }
+
+    /// Replaces the metrics file, if one is configured.
+    pub fn write_metrics(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.metrics_path else {
+            return Ok(());
+        };
+        // Written next to the file and renamed, so a collector never reads half of it
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.metrics())?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+fn fetch_blockhash(rpc_client: &RpcClient) -> anyhow::Result<CachedBlockhash> {
+    let response: Response<RpcBlockhash> = rpc_client.send(
+        RpcRequest::GetLatestBlockhash,
+        json!([rpc_client.commitment()]),
+    )?;
+    Ok(CachedBlockhash {
+        hash: Hash::from_str(&response.value.blockhash)?,
+        last_valid_block_height: response.value.last_valid_block_height,
+        slot: response.context.slot,
+        fetched_at: Instant::now(),
+    })
+}
+
+pub async fn blockhash_service_loop(service: Arc<BlockhashService>, mut shutdown: Shutdown) {
+    while !shutdown.is_triggered() {
+        if let Err(e) = service.poll() {
+            error!("Failed to refresh blockhash: {:?}", e);
+        }
+        if let Err(e) = service.write_metrics() {
+            error!("Failed to write blockhash metrics: {:?}", e);
+        }
+        shutdown.sleep(service.poll_interval).await;
+    }
+}
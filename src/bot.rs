@@ -1,12 +1,33 @@
-use crate::config::Config;
+use crate::base_assets::BaseAssets;
+use crate::blockhash::{blockhash_service_loop, BlockhashService};
+use crate::config::{Config, CycleConfig, MintConfig, RoutingConfig};
+use crate::constants::sol_mint;
+use crate::cycles::scan_cycles;
+use crate::ledger::{Ledger, DEFAULT_LEDGER_PATH};
+use crate::lookup_table::LookupTableManager;
+use crate::nonce::{ensure_nonce_accounts, nonce_refresher, NoncePool};
+use crate::pools::{DexKind, MintPoolData};
+use crate::quote::constant_product_fee_bps;
 use crate::refresh::initialize_pool_data;
-use crate::transaction::build_and_send_transaction;
+use crate::reload::{diff_routing, ReloadTrigger, DEFAULT_RELOAD_INTERVAL};
+use crate::risk::{balance_monitor_loop, kill_signal_listener, settlement_loop, RiskManager};
+use crate::rpc::{AccountSource, TxSender};
+use crate::rpc_pool::{endpoint_label, health_check_loop, RpcPool};
+use crate::sender::SendingEndpoint;
+use crate::supervisor::{Shutdown, Supervisor, SupervisorHandle};
+use crate::tpu::{leader_tracker_loop, LeaderTracker, TpuSender};
+use crate::transaction::{
+    build_and_send_transaction, executable_pools, split_by_base, swap_lookup_accounts,
+    PoolSelections, SentTransaction, TxLifetime,
+};
+use crate::wallets::{WalletSigner, Wallets};
+use crate::wsol::{wsol_manager_loop, WsolManager};
 use anyhow::Context;
+use futures::future::join_all;
+use futures::FutureExt;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::address_lookup_table::AddressLookupTableAccount;
-use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
 use solana_sdk::{
     address_lookup_table::state::AddressLookupTable, compute_budget::ComputeBudgetInstruction,
@@ -14,6 +35,7 @@ use solana_sdk::{
 use spl_associated_token_account::{
     get_associated_token_address, get_associated_token_address_with_program_id,
 };
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -22,268 +44,827 @@ use tracing::{error, info, warn};
 
 pub async fn run_bot(config_path: &str) -> anyhow::Result<()> {
     let config = Config::load(config_path)?;
+    let base_assets = Arc::new(BaseAssets::from_config(&config)?);
     info!("Configuration loaded successfully");
 
-    let rpc_client = Arc::new(RpcClient::new(config.rpc.url.clone()));
-
-    let sending_rpc_clients = if let Some(spam_config) = &config.spam {
-        if spam_config.enabled {
-            spam_config
-                .sending_rpc_urls
-                .iter()
-                .map(|url| Arc::new(RpcClient::new(url.clone())))
-                .collect::<Vec<_>>()
-        } else {
-            vec![rpc_client.clone()]
-        }
-    } else {
-        vec![rpc_client.clone()]
+    let rpc_pool = Arc::new(RpcPool::new(&config.rpc));
+    rpc_pool.check();
+    rpc_pool.log_scores();
+
+    let wallets = Arc::new(Wallets::load(&config.wallet)?);
+    for wallet in wallets.pubkeys() {
+        info!("Wallet loaded: {}", wallet);
+    }
+    for fee_payer in wallets.fee_payers() {
+        info!("Fee payer loaded: {}", fee_payer.pubkey());
+    }
+    let wallet_kp = wallets.primary().clone();
+
+    let ledger_path = config
+        .bot
+        .ledger_path
+        .clone()
+        .unwrap_or_else(|| DEFAULT_LEDGER_PATH.to_string());
+    let ledger = Arc::new(Ledger::open(&ledger_path).context("Failed to open ledger")?);
+    info!("Ledger: {}", ledger_path);
+
+    let fallback_clients = config
+        .blockhash
+        .as_ref()
+        .and_then(|b| b.fallback_rpc_urls.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|url| Arc::new(RpcClient::new(url.clone())))
+        .collect();
+    let blockhash_service = Arc::new(BlockhashService::new(
+        rpc_pool.clone(),
+        fallback_clients,
+        config.blockhash.as_ref(),
+    ));
+    blockhash_service.refresh()?;
+
+    let spam_config = config.spam.as_ref().filter(|s| s.enabled);
+    let sending_endpoints: Vec<(String, Arc<dyn TxSender>)> = match spam_config {
+        Some(spam_config) => spam_config
+            .sending_rpc_urls
+            .iter()
+            .map(|url| {
+                let client = Arc::new(RpcClient::new(url.clone())) as Arc<dyn TxSender>;
+                (endpoint_label(url), client)
+            })
+            .collect(),
+        None => vec![(
+            endpoint_label(&config.rpc.url),
+            rpc_pool.clone() as Arc<dyn TxSender>,
+        )],
     };
+    let sending_rpc_clients = sending_endpoints
+        .into_iter()
+        .map(|(label, sender)| {
+            Arc::new(SendingEndpoint::new(
+                label,
+                sender,
+                blockhash_service.clone(),
+                spam_config,
+            )) as Arc<dyn TxSender>
+        })
+        .collect::<Vec<_>>();
 
-    let wallet_kp =
-        load_keypair(&config.wallet.private_key).context("Failed to load wallet keypair")?;
-    info!("Wallet loaded: {}", wallet_kp.pubkey());
+    let mut supervisor = Supervisor::new();
 
-    let initial_blockhash = rpc_client.get_latest_blockhash()?;
-    let cached_blockhash = Arc::new(Mutex::new(initial_blockhash));
+    let health_pool = rpc_pool.clone();
+    supervisor.spawn("rpc-health", move |shutdown| {
+        health_check_loop(health_pool.clone(), shutdown).boxed()
+    });
 
-    let refresh_interval = Duration::from_secs(10);
-    let blockhash_client = rpc_client.clone();
-    let blockhash_cache = cached_blockhash.clone();
-    tokio::spawn(async move {
-        blockhash_refresher(blockhash_client, blockhash_cache, refresh_interval).await;
+    let refresher_service = blockhash_service.clone();
+    supervisor.spawn("blockhash-refresher", move |shutdown| {
+        blockhash_service_loop(refresher_service.clone(), shutdown).boxed()
     });
 
-    for mint_config in &config.routing.mint_config_list {
-        // Get the mint account info to check owner
-        let mint_owner = rpc_client
-            .get_account(&Pubkey::from_str(&mint_config.mint).unwrap())
-            .unwrap()
-            .owner;
-        let wallet_token_account = get_associated_token_address_with_program_id(
-            &wallet_kp.pubkey(),
-            &Pubkey::from_str(&mint_config.mint).unwrap(),
-            &mint_owner,
-        );
+    let nonce_pool = match config.nonce.as_ref().filter(|n| n.enabled) {
+        Some(nonce_config) => {
+            let addresses =
+                ensure_nonce_accounts(&rpc_pool, wallet_kp.as_ref(), nonce_config.count, &ledger)?;
+            let nonce_pool = Arc::new(NoncePool::new(
+                wallet_kp.pubkey(),
+                addresses,
+                Duration::from_millis(nonce_config.reuse_after.unwrap_or(5_000)),
+            ));
+            nonce_pool.refresh(rpc_pool.as_ref())?;
 
-        println!("   Token mint: {}", mint_config.mint);
-        println!("   Wallet token ATA: {}", wallet_token_account);
-        // Check if the PWEASE token account exists and create it if it doesn't
-        println!("\n   Checking if token account exists...");
-        loop {
-            match rpc_client.get_account(&wallet_token_account) {
-                Ok(_) => {
-                    println!("   token account exists!");
-                    break;
-                }
-                Err(_) => {
-                    println!("   token account does not exist. Creating it...");
-
-                    // Create the instruction to create the associated token account
-                    let create_ata_ix =
-                            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
-                                &wallet_kp.pubkey(), // Funding account
-                                &wallet_kp.pubkey(), // Wallet account
-                                &Pubkey::from_str(&mint_config.mint).unwrap(),   // Token mint
-                                &spl_token::ID,      // Token program
-                            );
-
-                    // Get a recent blockhash
-                    let blockhash = rpc_client.get_latest_blockhash()?;
-
-                    let compute_unit_price_ix =
-                        ComputeBudgetInstruction::set_compute_unit_price(1_000_000);
-                    let compute_unit_limit_ix =
-                        ComputeBudgetInstruction::set_compute_unit_limit(60_000);
-
-                    // Create the transaction
-                    let create_ata_tx = solana_sdk::transaction::Transaction::new_signed_with_payer(
-                        &[compute_unit_price_ix, compute_unit_limit_ix, create_ata_ix],
-                        Some(&wallet_kp.pubkey()),
-                        &[&wallet_kp],
-                        blockhash,
-                    );
+            let refresh_interval =
+                Duration::from_millis(nonce_config.refresh_interval.unwrap_or(400));
+            let nonce_rpc_pool = rpc_pool.clone();
+            let refresher_pool = nonce_pool.clone();
+            supervisor.spawn("nonce-refresher", move |shutdown| {
+                nonce_refresher(
+                    nonce_rpc_pool.clone(),
+                    refresher_pool.clone(),
+                    refresh_interval,
+                    shutdown,
+                )
+                .boxed()
+            });
+            info!(
+                "Durable nonce mode enabled with {} nonce accounts",
+                nonce_config.count
+            );
+            Some(nonce_pool)
+        }
+        None => None,
+    };
 
-                    // Send the transaction
-                    match rpc_client.send_and_confirm_transaction(&create_ata_tx) {
-                        Ok(sig) => {
-                            println!("   token account created successfully! Signature: {}", sig);
-                        }
-                        Err(e) => {
-                            println!("   Failed to create token account: {:?}", e);
-                            return Err(anyhow::anyhow!("Failed to create token account"));
-                        }
-                    }
-                }
+    let tpu_sender = match config.tpu.as_ref().filter(|t| t.enabled) {
+        Some(tpu_config) => {
+            let tracker = Arc::new(LeaderTracker::new(rpc_pool.clone(), tpu_config));
+            tracker.refresh()?;
+            let loop_tracker = tracker.clone();
+            supervisor.spawn("leader-tracker", move |shutdown| {
+                leader_tracker_loop(loop_tracker.clone(), shutdown).boxed()
+            });
+            info!("Sending to the TPU of upcoming leaders enabled");
+            Some(Arc::new(TpuSender::new(tracker)) as Arc<dyn TxSender>)
+        }
+        None => None,
+    };
+
+    for wallet in wallets.all() {
+        for mint_config in &config.routing.mint_config_list {
+            ensure_token_account(
+                &rpc_pool,
+                wallets.fee_payer_for(wallet).as_ref(),
+                wallet.as_ref(),
+                &mint_config.mint,
+            )?;
+        }
+    }
+
+    if let Some(wsol_config) = config.wsol.as_ref().filter(|w| w.enabled) {
+        let manage_balance = !config.flashloan.as_ref().is_some_and(|k| k.enabled);
+        if !manage_balance {
+            info!("Flashloan enabled, WSOL balance will not be managed");
+        }
+        for wallet in wallets.all() {
+            let wsol_manager = Arc::new(WsolManager::new(
+                rpc_pool.clone(),
+                wallet.clone(),
+                wsol_config.clone(),
+                ledger.clone(),
+            ));
+            wsol_manager.ensure_account()?;
+
+            if manage_balance {
+                let check_interval =
+                    Duration::from_millis(wsol_config.check_interval.unwrap_or(30_000));
+                supervisor.spawn(
+                    format!("wsol-manager-{}", wallet.pubkey()),
+                    move |shutdown| {
+                        wsol_manager_loop(wsol_manager.clone(), check_interval, shutdown).boxed()
+                    },
+                );
             }
         }
     }
 
+    let risk_manager = Arc::new(RiskManager::new(
+        config.risk.clone(),
+        rpc_pool.clone(),
+        wallets
+            .pubkeys()
+            .into_iter()
+            .chain(wallets.fee_payers().iter().map(|f| f.pubkey()))
+            .collect(),
+        ledger.clone(),
+    )?);
+    // Supervised so it has stopped before the pending sends are drained on shutdown
+    let settlement_risk = risk_manager.clone();
+    supervisor.spawn("settlement", move |shutdown| {
+        settlement_loop(settlement_risk.clone(), shutdown).boxed()
+    });
+    let kill_signal_risk = risk_manager.clone();
+    supervisor.spawn("kill-signal", move |shutdown| {
+        kill_signal_listener(kill_signal_risk.clone(), shutdown).boxed()
+    });
+    if risk_manager.limits_enabled() {
+        let monitor_risk = risk_manager.clone();
+        supervisor.spawn("balance-monitor", move |shutdown| {
+            balance_monitor_loop(monitor_risk.clone(), shutdown).boxed()
+        });
+        info!("Risk limits enabled");
+    }
+
+    let lookup_table_manager = match config.lookup_table.as_ref().filter(|l| l.enabled) {
+        Some(lookup_table_config) => Some(Arc::new(Mutex::new(LookupTableManager::new(
+            rpc_pool.clone(),
+            wallet_kp.clone(),
+            lookup_table_config,
+            ledger.clone(),
+        )?))),
+        None => None,
+    };
+
+    let supervisor_handle = supervisor.handle();
+    let ctx = BotContext {
+        config: config.clone(),
+        rpc_pool: rpc_pool.clone(),
+        sending_rpc_clients,
+        tpu_sender,
+        wallets,
+        blockhash_service,
+        nonce_pool,
+        risk_manager: risk_manager.clone(),
+        lookup_table_manager,
+        base_assets,
+    };
+
+    let mut mint_tasks = HashMap::new();
     for mint_config in &config.routing.mint_config_list {
-        info!("Processing mint: {}", mint_config.mint);
-
-        let pool_data = initialize_pool_data(
-            &mint_config.mint,
-            &wallet_kp.pubkey().to_string(),
-            mint_config.raydium_pool_list.as_ref(),
-            mint_config.raydium_cp_pool_list.as_ref(),
-            mint_config.pump_pool_list.as_ref(),
-            mint_config.meteora_dlmm_pool_list.as_ref(),
-            mint_config.whirlpool_pool_list.as_ref(),
-            mint_config.raydium_clmm_pool_list.as_ref(),
-            mint_config.meteora_damm_pool_list.as_ref(),
-            mint_config.solfi_pool_list.as_ref(),
-            mint_config.meteora_damm_v2_pool_list.as_ref(),
-            mint_config.vertigo_pool_list.as_ref(),
-            mint_config.heaven_pool_list.as_ref(),
-            rpc_client.clone(),
-        )
-        .await?;
-
-        let mint_pool_data = Arc::new(Mutex::new(pool_data));
-
-        // TODO: Add logic to periodically refresh pool data
-
-        let config_clone = config.clone();
-        let mint_config_clone = mint_config.clone();
-        let sending_rpc_clients_clone = sending_rpc_clients.clone();
-        let cached_blockhash_clone = cached_blockhash.clone();
-        let wallet_bytes = wallet_kp.to_bytes();
-        let wallet_kp_clone = Keypair::from_bytes(&wallet_bytes).unwrap();
-        let mut lookup_table_accounts = mint_config_clone.lookup_table_accounts.unwrap_or_default();
-        lookup_table_accounts.push("4sKLJ1Qoudh8PJyqBeuKocYdsZvxTcRShUt9aKqwhgvC".to_string());
-
-        let mut lookup_table_accounts_list = vec![];
-
-        for lookup_table_account in lookup_table_accounts {
-            match Pubkey::from_str(&lookup_table_account) {
-                Ok(pubkey) => {
-                    match rpc_client.get_account(&pubkey) {
-                        Ok(account) => {
-                            match AddressLookupTable::deserialize(&account.data) {
-                                Ok(lookup_table) => {
-                                    let lookup_table_account = AddressLookupTableAccount {
-                                        key: pubkey,
-                                        addresses: lookup_table.addresses.into_owned(),
-                                    };
-                                    lookup_table_accounts_list.push(lookup_table_account);
-                                    info!("   Successfully loaded lookup table: {}", pubkey);
-                                }
-                                Err(e) => {
-                                    error!(
-                                        "   Failed to deserialize lookup table {}: {}",
-                                        pubkey, e
-                                    );
-                                    continue; // Skip this lookup table but continue processing others
-                                }
+        let mint_task = prepare_mint(&ctx, mint_config).await?;
+        spawn_mint_task(&supervisor_handle, mint_task.clone());
+        mint_tasks.insert(mint_config.mint.clone(), mint_task);
+    }
+
+    let mint_tasks = Arc::new(Mutex::new(mint_tasks));
+
+    if let Some(cycle_config) = config.cycles.clone().filter(|c| c.enabled) {
+        let scanner_rpc_pool = rpc_pool.clone();
+        let scanner_tasks = mint_tasks.clone();
+        supervisor.spawn("cycle-scanner", move |shutdown| {
+            cycle_scanner(
+                scanner_rpc_pool.clone(),
+                scanner_tasks.clone(),
+                cycle_config.clone(),
+                shutdown,
+            )
+            .boxed()
+        });
+    }
+
+    let reload_interval = match config.bot.reload_interval {
+        Some(0) => None,
+        Some(ms) => Some(Duration::from_millis(ms)),
+        None => Some(DEFAULT_RELOAD_INTERVAL),
+    };
+    let watcher = ConfigWatcher {
+        config_path: config_path.to_string(),
+        reload_interval,
+        ctx,
+        supervisor: supervisor_handle,
+        mint_tasks,
+    };
+    supervisor.spawn("config-watcher", move |shutdown| {
+        watcher.clone().run(shutdown).boxed()
+    });
+
+    supervisor.run().await?;
+
+    risk_manager.wait_for_pending(Duration::from_secs(60)).await;
+    ledger.flush();
+    info!("Shutdown complete");
+
+    Ok(())
+}
+
+/// Loads the pools and lookup tables of a mint. Nothing is spawned, so a failure leaves running tasks untouched.
+async fn prepare_mint(ctx: &BotContext, mint_config: &MintConfig) -> anyhow::Result<MintTask> {
+    info!("Processing mint: {}", mint_config.mint);
+
+    let pool_data = initialize_pool_data(
+        &mint_config.mint,
+        &ctx.wallets.primary().pubkey().to_string(),
+        mint_config.raydium_pool_list.as_ref(),
+        mint_config.raydium_cp_pool_list.as_ref(),
+        mint_config.pump_pool_list.as_ref(),
+        mint_config.meteora_dlmm_pool_list.as_ref(),
+        mint_config.whirlpool_pool_list.as_ref(),
+        mint_config.raydium_clmm_pool_list.as_ref(),
+        mint_config.meteora_damm_pool_list.as_ref(),
+        mint_config.solfi_pool_list.as_ref(),
+        mint_config.meteora_damm_v2_pool_list.as_ref(),
+        mint_config.vertigo_pool_list.as_ref(),
+        mint_config.heaven_pool_list.as_ref(),
+        ctx.rpc_pool.clone(),
+    )
+    .await?;
+
+    let mut lookup_table_accounts = mint_config
+        .lookup_table_accounts
+        .clone()
+        .unwrap_or_default();
+    lookup_table_accounts.push("4sKLJ1Qoudh8PJyqBeuKocYdsZvxTcRShUt9aKqwhgvC".to_string());
+
+    let mut lookup_table_accounts_list = vec![];
+
+    for lookup_table_account in lookup_table_accounts {
+        match Pubkey::from_str(&lookup_table_account) {
+            Ok(pubkey) => {
+                match ctx.rpc_pool.get_account(&pubkey) {
+                    Ok(account) => {
+                        match AddressLookupTable::deserialize(&account.data) {
+                            Ok(lookup_table) => {
+                                let lookup_table_account = AddressLookupTableAccount {
+                                    key: pubkey,
+                                    addresses: lookup_table.addresses.into_owned(),
+                                };
+                                lookup_table_accounts_list.push(lookup_table_account);
+                                info!("   Successfully loaded lookup table: {}", pubkey);
+                            }
+                            Err(e) => {
+                                error!("   Failed to deserialize lookup table {}: {}", pubkey, e);
+                                continue; // Skip this lookup table but continue processing others
                             }
-                        }
-                        Err(e) => {
-                            error!("   Failed to fetch lookup table account {}: {}", pubkey, e);
-                            continue; // Skip this lookup table but continue processing others
                         }
                     }
-                }
-                Err(e) => {
-                    error!(
-                        "   Invalid lookup table pubkey string {}: {}",
-                        lookup_table_account, e
-                    );
-                    continue; // Skip this lookup table but continue processing others
+                    Err(e) => {
+                        error!("   Failed to fetch lookup table account {}: {}", pubkey, e);
+                        continue; // Skip this lookup table but continue processing others
+                    }
                 }
             }
+            Err(e) => {
+                error!(
+                    "   Invalid lookup table pubkey string {}: {}",
+                    lookup_table_account, e
+                );
+                continue; // Skip this lookup table but continue processing others
+            }
         }
-        if lookup_table_accounts_list.is_empty() {
-            warn!("   Warning: No valid lookup tables were loaded");
-        } else {
-            info!(
-                "   Loaded {} lookup tables successfully",
-                lookup_table_accounts_list.len()
+    }
+
+    let base_assets = &ctx.base_assets;
+    let executable_pool_data = executable_pools(&pool_data, base_assets);
+    let bridge = base_assets.bridged(executable_pool_data.pools().iter().map(|p| &p.base_mint));
+    if let Some(base_asset) = bridge {
+        for wallet in ctx.wallets.all() {
+            ensure_token_account(
+                &ctx.rpc_pool,
+                ctx.wallets.fee_payer_for(wallet).as_ref(),
+                wallet.as_ref(),
+                &base_asset.mint.to_string(),
+            )?;
+        }
+    }
+
+    if let Some(lookup_table_manager) = &ctx.lookup_table_manager {
+        let use_flashloan = ctx.config.flashloan.as_ref().is_some_and(|k| k.enabled);
+        let groups = split_by_base(&executable_pool_data, use_flashloan);
+        // Every wallet has its own token accounts
+        let mut swap_accounts = Vec::new();
+        for wallet in ctx.wallets.all() {
+            for group in &groups {
+                swap_accounts.extend(swap_lookup_accounts(
+                    wallet.as_ref(),
+                    group,
+                    base_assets,
+                    ctx.config.bot.compute_unit_limit,
+                    use_flashloan,
+                )?);
+            }
+        }
+        if let Err(e) = lookup_table_manager
+            .lock()
+            .await
+            .cover(&swap_accounts, &mut lookup_table_accounts_list)
+            .await
+        {
+            warn!(
+                "   Failed to add missing accounts of mint {} to the lookup table: {:?}",
+                mint_config.mint, e
             );
         }
+    }
+
+    if lookup_table_accounts_list.is_empty() {
+        warn!("   Warning: No valid lookup tables were loaded");
+    } else {
+        info!(
+            "   Loaded {} lookup tables successfully",
+            lookup_table_accounts_list.len()
+        );
+    }
+
+    let mint_pool_data = Arc::new(Mutex::new(pool_data));
+
+    // TODO: Add logic to periodically refresh pool data
 
-        tokio::spawn(async move {
-            let process_delay = Duration::from_millis(mint_config_clone.process_delay);
+    Ok(MintTask {
+        config: ctx.config.clone(),
+        mint_config: mint_config.clone(),
+        mint_pool_data,
+        wallets: ctx.wallets.clone(),
+        sending_rpc_clients: ctx.sending_rpc_clients.clone(),
+        tpu_sender: ctx.tpu_sender.clone(),
+        blockhash_service: ctx.blockhash_service.clone(),
+        nonce_pool: ctx.nonce_pool.clone(),
+        lookup_table_accounts: Arc::new(lookup_table_accounts_list),
+        risk_manager: ctx.risk_manager.clone(),
+        base_assets: ctx.base_assets.clone(),
+        pool_selections: Arc::new(PoolSelections::default()),
+    })
+}
 
-            loop {
-                let latest_blockhash = {
-                    let guard = cached_blockhash_clone.lock().await;
-                    *guard
-                };
+fn mint_task_name(mint: &str) -> String {
+    format!("mint-{}", mint)
+}
 
-                let guard = mint_pool_data.lock().await;
+fn spawn_mint_task(supervisor: &SupervisorHandle, mint_task: MintTask) {
+    supervisor.spawn(
+        mint_task_name(&mint_task.mint_config.mint),
+        move |shutdown| mint_task.clone().run(shutdown).boxed(),
+    );
+}
+
+/// Applies routing changes from the config file while the bot runs.
+#[derive(Clone)]
+struct ConfigWatcher {
+    config_path: String,
+    reload_interval: Option<Duration>,
+    ctx: BotContext,
+    supervisor: SupervisorHandle,
+    mint_tasks: Arc<Mutex<HashMap<String, MintTask>>>,
+}
+
+impl ConfigWatcher {
+    async fn run(self, mut shutdown: Shutdown) {
+        let mut trigger = match ReloadTrigger::new(&self.config_path, self.reload_interval) {
+            Ok(trigger) => trigger,
+            Err(e) => {
+                error!("Failed to watch config file: {:?}", e);
+                return;
+            }
+        };
 
-                match build_and_send_transaction(
-                    &wallet_kp_clone,
-                    &config_clone,
+        loop {
+            tokio::select! {
+                _ = shutdown.wait() => return,
+                _ = trigger.next() => {}
+            }
+
+            info!("Reloading routing config from {}", self.config_path);
+            if let Err(e) = self.reload().await {
+                error!("Config reload rejected, keeping current routing: {:?}", e);
+            }
+        }
+    }
+
+    async fn reload(&self) -> anyhow::Result<()> {
+        let new_config = Config::load(&self.config_path)?;
+
+        let mut mint_tasks = self.mint_tasks.lock().await;
+        let current = RoutingConfig {
+            mint_config_list: mint_tasks.values().map(|t| t.mint_config.clone()).collect(),
+        };
+        let changes = diff_routing(&current, &new_config.routing);
+        if changes.is_empty() {
+            info!("Routing config unchanged");
+            return Ok(());
+        }
+
+        // Build every new task before touching the running ones
+        let mut prepared = Vec::new();
+        for mint_config in changes.added.iter().chain(&changes.reinitialized) {
+            info!("Loading pools for mint {}", mint_config.mint);
+            prepared.push(prepare_mint(&self.ctx, mint_config).await?);
+        }
+        for mint_config in &changes.added {
+            for wallet in self.ctx.wallets.all() {
+                ensure_token_account(
+                    &self.ctx.rpc_pool,
+                    self.ctx.wallets.fee_payer_for(wallet).as_ref(),
+                    wallet.as_ref(),
+                    &mint_config.mint,
+                )?;
+            }
+        }
+        for mint_config in &changes.updated {
+            let mut mint_task = mint_tasks[&mint_config.mint].clone();
+            mint_task.mint_config = mint_config.clone();
+            prepared.push(mint_task);
+        }
+
+        for mint in &changes.removed {
+            info!("Removing mint {}", mint);
+            self.supervisor.stop(mint_task_name(mint));
+            mint_tasks.remove(mint);
+        }
+        for mint_task in prepared {
+            let mint = mint_task.mint_config.mint.clone();
+            if mint_tasks.contains_key(&mint) {
+                self.supervisor.stop(mint_task_name(&mint));
+            }
+            spawn_mint_task(&self.supervisor, mint_task.clone());
+            mint_tasks.insert(mint, mint_task);
+        }
+
+        info!(
+            "Routing reloaded: {} added, {} removed, {} reinitialized, {} updated",
+            changes.added.len(),
+            changes.removed.len(),
+            changes.reinitialized.len(),
+            changes.updated.len()
+        );
+        Ok(())
+    }
+}
+
+/// Shared state every mint task is built from.
+#[derive(Clone)]
+struct BotContext {
+    config: Config,
+    rpc_pool: Arc<RpcPool>,
+    sending_rpc_clients: Vec<Arc<dyn TxSender>>,
+    tpu_sender: Option<Arc<dyn TxSender>>,
+    wallets: Arc<Wallets>,
+    blockhash_service: Arc<BlockhashService>,
+    nonce_pool: Option<Arc<NoncePool>>,
+    risk_manager: Arc<RiskManager>,
+    lookup_table_manager: Option<Arc<Mutex<LookupTableManager>>>,
+    base_assets: Arc<BaseAssets>,
+}
+
+/// Everything a mint task needs to build and send its transactions.
+#[derive(Clone)]
+struct MintTask {
+    config: Config,
+    mint_config: MintConfig,
+    mint_pool_data: Arc<Mutex<MintPoolData>>,
+    wallets: Arc<Wallets>,
+    sending_rpc_clients: Vec<Arc<dyn TxSender>>,
+    tpu_sender: Option<Arc<dyn TxSender>>,
+    blockhash_service: Arc<BlockhashService>,
+    nonce_pool: Option<Arc<NoncePool>>,
+    lookup_table_accounts: Arc<Vec<AddressLookupTableAccount>>,
+    risk_manager: Arc<RiskManager>,
+    base_assets: Arc<BaseAssets>,
+    /// Pools picked per route or base when not all pools fit in one transaction.
+    pool_selections: Arc<PoolSelections>,
+}
+
+impl MintTask {
+    async fn run(self, mut shutdown: Shutdown) {
+        let process_delay = Duration::from_millis(self.mint_config.process_delay);
+        let routes = self.routes();
+        let senders = self.senders();
+
+        let executable_pool_count =
+            executable_pools(&*self.mint_pool_data.lock().await, &self.base_assets)
+                .pools()
+                .len();
+        if executable_pool_count < 2 {
+            warn!(
+                "Mint {} has fewer than two pools with a base the executor can trade, not sending",
+                self.mint_config.mint
+            );
+            shutdown.wait().await;
+            return;
+        }
+
+        while !shutdown.is_triggered() {
+            if self.risk_manager.check().is_err() {
+                shutdown.sleep(process_delay).await;
+                continue;
+            }
+
+            let lifetime = match &self.nonce_pool {
+                Some(nonce_pool) => TxLifetime::Nonce(nonce_pool),
+                None => match self.blockhash_service.fresh() {
+                    Ok(blockhash) => TxLifetime::Blockhash(blockhash),
+                    Err(e) => {
+                        warn!("Not sending for mint {}: {}", self.mint_config.mint, e);
+                        shutdown.sleep(process_delay).await;
+                        continue;
+                    }
+                },
+            };
+
+            let guard = self.mint_pool_data.lock().await;
+
+            if routes.is_empty() {
+                let wallet_kp = self.wallets.for_send(&self.mint_config.mint);
+                let result = build_and_send_transaction(
+                    wallet_kp.as_ref(),
+                    self.wallets.fee_payer_for(&wallet_kp).as_ref(),
+                    &self.config,
+                    &self.base_assets,
                     &*guard, // Dereference the guard here
-                    &sending_rpc_clients_clone,
-                    latest_blockhash,
-                    &lookup_table_accounts_list,
+                    &self.pool_selections,
+                    0,
+                    &senders,
+                    lifetime,
+                    &self.lookup_table_accounts,
                 )
-                .await
-                {
-                    Ok(signatures) => {
-                        info!(
-                            "Transactions sent successfully for mint {}",
-                            mint_config_clone.mint
-                        );
-                        for signature in signatures {
-                            info!("  Signature: {}", signature);
-                        }
-                    }
-                    Err(e) => {
-                        error!(
-                            "Error sending transaction for mint {}: {}",
-                            mint_config_clone.mint, e
-                        );
+                .await;
+                self.handle_send_result(None, wallet_kp.pubkey(), result);
+            } else {
+                let task = &self;
+                let senders = &senders;
+                let sends = routes.iter().map(|route| {
+                    let route_pool_data = guard.with_pools(&route.pools);
+                    let wallet_kp = task.wallets.for_send(&task.mint_config.mint);
+                    async move {
+                        let result = build_and_send_transaction(
+                            wallet_kp.as_ref(),
+                            task.wallets.fee_payer_for(&wallet_kp).as_ref(),
+                            &task.config,
+                            &task.base_assets,
+                            &route_pool_data,
+                            &task.pool_selections,
+                            route.minimum_profit,
+                            senders,
+                            lifetime,
+                            &task.lookup_table_accounts,
+                        )
+                        .await;
+                        (route, wallet_kp.pubkey(), result)
                     }
+                });
+                for (route, wallet, result) in join_all(sends).await {
+                    self.handle_send_result(Some(&route.name), wallet, result);
                 }
-
-                tokio::time::sleep(process_delay).await;
             }
-        });
+            drop(guard);
+
+            shutdown.sleep(process_delay).await;
+        }
+
+        info!("Stopped sending for mint {}", self.mint_config.mint);
     }
 
-    loop {
-        tokio::time::sleep(Duration::from_secs(1)).await;
+    /// The RPC senders, and the TPU sender unless the mint opted out of it.
+    fn senders(&self) -> Vec<Arc<dyn TxSender>> {
+        let mut senders = self.sending_rpc_clients.clone();
+        if self.mint_config.use_tpu != Some(false) {
+            senders.extend(self.tpu_sender.clone());
+        }
+        senders
+    }
+
+    fn routes(&self) -> Vec<Route> {
+        self.mint_config
+            .routes
+            .iter()
+            .flatten()
+            .map(|route| Route {
+                name: route.name.clone(),
+                pools: route
+                    .pools
+                    .iter()
+                    .filter_map(|pool| Pubkey::from_str(pool).ok())
+                    .collect(),
+                minimum_profit: route.minimum_profit,
+            })
+            .collect()
+    }
+
+    fn handle_send_result(
+        &self,
+        route: Option<&str>,
+        wallet: Pubkey,
+        result: anyhow::Result<Vec<SentTransaction>>,
+    ) {
+        let target = match route {
+            Some(route) => format!("mint {} route {}", self.mint_config.mint, route),
+            None => format!("mint {}", self.mint_config.mint),
+        };
+        match result {
+            Ok(sent) => {
+                info!("Transactions sent successfully for {}", target);
+                // Every RPC receives the same transaction, so each signature is tracked once
+                let mut tracked = HashSet::new();
+                for tx in sent.iter().filter(|tx| tracked.insert(tx.signature)) {
+                    self.risk_manager
+                        .track(tx.signature, &self.mint_config.mint, wallet, tx.nonce);
+                }
+                for tx in sent {
+                    info!("  Signature: {}", tx.signature);
+                }
+            }
+            Err(e) => {
+                error!("Error sending transaction for {}: {}", target, e);
+            }
+        }
     }
 }
 
-async fn blockhash_refresher(
-    rpc_client: Arc<RpcClient>,
-    cached_blockhash: Arc<Mutex<Hash>>,
-    refresh_interval: Duration,
+/// Pools of a mint that are sent together in their own transaction.
+struct Route {
+    name: String,
+    pools: HashSet<Pubkey>,
+    minimum_profit: u64,
+}
+
+/// Logs profitable cycles across all mints. The executor only trades
+/// base -> token -> base, so these are reported and not sent.
+async fn cycle_scanner(
+    rpc_pool: Arc<RpcPool>,
+    mint_tasks: Arc<Mutex<HashMap<String, MintTask>>>,
+    config: CycleConfig,
+    mut shutdown: Shutdown,
 ) {
-    loop {
-        match rpc_client.get_latest_blockhash() {
-            Ok(blockhash) => {
-                let mut guard = cached_blockhash.lock().await;
-                *guard = blockhash;
-                info!("Blockhash refreshed: {}", blockhash);
+    let scan_interval = Duration::from_millis(config.scan_interval.unwrap_or(5_000));
+    let sol = sol_mint();
+    let mut unquoted_reported = HashSet::new();
+
+    while !shutdown.is_triggered() {
+        let mut pools = Vec::new();
+        for mint_task in mint_tasks.lock().await.values() {
+            pools.extend(mint_task.mint_pool_data.lock().await.pools());
+        }
+
+        let mut unquoted: HashMap<DexKind, usize> = HashMap::new();
+        for pool in pools
+            .iter()
+            .filter(|p| constant_product_fee_bps(p.dex).is_none())
+        {
+            *unquoted.entry(pool.dex).or_default() += 1;
+        }
+        for (dex, count) in unquoted {
+            if unquoted_reported.insert(dex) {
+                warn!(
+                    "Cycle scanner leaves out {} {} pool(s): only raydium, raydium_cp and pump pools are quoted",
+                    count,
+                    dex.name()
+                );
+            }
+        }
+
+        match scan_cycles(rpc_pool.as_ref(), &pools, &sol, &config) {
+            Ok(opportunities) => {
+                for opportunity in opportunities {
+                    let path: Vec<String> =
+                        opportunity.mints.iter().map(|m| m.to_string()).collect();
+                    let pools: Vec<String> =
+                        opportunity.pools.iter().map(|p| p.to_string()).collect();
+                    info!(
+                        "Cycle {} quoted {} -> {} lamports (profit {}) via pools {}",
+                        path.join(" -> "),
+                        opportunity.amount_in,
+                        opportunity.amount_out,
+                        opportunity.profit(),
+                        pools.join(", ")
+                    );
+                }
             }
             Err(e) => {
-                error!("Failed to refresh blockhash: {:?}", e);
+                error!("Failed to scan for cycles: {:?}", e);
             }
         }
-        tokio::time::sleep(refresh_interval).await;
+
+        shutdown.sleep(scan_interval).await;
     }
 }
 
-fn load_keypair(private_key: &str) -> anyhow::Result<Keypair> {
-    if let Ok(keypair) = bs58::decode(private_key)
-        .into_vec()
-        .map_err(|e| anyhow::anyhow!("Failed to decode base58: {}", e))
-        .and_then(|bytes| {
-            Keypair::from_bytes(&bytes).map_err(|e| anyhow::anyhow!("Invalid keypair bytes: {}", e))
-        })
-    {
-        return Ok(keypair);
-    }
+/// Creates the wallet token account for `mint` if it does not exist yet, paid by `fee_payer`.
+/// The accounts are read through `AccountSource`; the creating transaction goes to the primary RPC.
+fn ensure_token_account(
+    rpc_pool: &RpcPool,
+    fee_payer: &WalletSigner,
+    wallet_kp: &WalletSigner,
+    mint: &str,
+) -> anyhow::Result<()> {
+    let mint = Pubkey::from_str(mint)?;
+    // Get the mint account info to check owner
+    let mint_owner = rpc_pool
+        .get_account(&mint)
+        .with_context(|| format!("Failed to fetch mint account {}", mint))?
+        .owner;
+    let wallet_token_account =
+        get_associated_token_address_with_program_id(&wallet_kp.pubkey(), &mint, &mint_owner);
+
+    println!("   Token mint: {}", mint);
+    println!("   Wallet token ATA: {}", wallet_token_account);
+    // Check if the PWEASE token account exists and create it if it doesn't
+    println!("\n   Checking if token account exists...");
+    loop {
+        match rpc_pool.get_account(&wallet_token_account) {
+            Ok(_) => {
+                println!("   token account exists!");
+                break;
+            }
+            Err(_) => {
+                println!("   token account does not exist. Creating it...");
+
+                // Create the instruction to create the associated token account
+                let create_ata_ix =
+                        spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                            &fee_payer.pubkey(), // Funding account
+                            &wallet_kp.pubkey(), // Wallet account
+                            &mint,   // Token mint
+                            &spl_token::ID,      // Token program
+                        );
+
+                // Get a recent blockhash
+                let blockhash = rpc_pool.primary().get_latest_blockhash()?;
+
+                let compute_unit_price_ix =
+                    ComputeBudgetInstruction::set_compute_unit_price(1_000_000);
+                let compute_unit_limit_ix =
+                    ComputeBudgetInstruction::set_compute_unit_limit(60_000);
 
-    if let Ok(keypair) = solana_sdk::signature::read_keypair_file(private_key) {
-        return Ok(keypair);
+                // Create the transaction
+                let mut create_ata_tx = solana_sdk::transaction::Transaction::new_with_payer(
+                    &[compute_unit_price_ix, compute_unit_limit_ix, create_ata_ix],
+                    Some(&fee_payer.pubkey()),
+                );
+                create_ata_tx.try_sign(&[fee_payer as &dyn Signer], blockhash)?;
+
+                // Send the transaction
+                match rpc_pool
+                    .primary()
+                    .send_and_confirm_transaction(&create_ata_tx)
+                {
+                    Ok(sig) => {
+                        println!("   token account created successfully! Signature: {}", sig);
+                    }
+                    Err(e) => {
+                        println!("   Failed to create token account: {:?}", e);
+                        return Err(anyhow::anyhow!("Failed to create token account"));
+                    }
+                }
+            }
+        }
     }
 
-    anyhow::bail!("Failed to load keypair from: {}", private_key)
+    Ok(())
 }
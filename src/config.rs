@@ -1,4 +1,8 @@
 use serde::{Deserialize, Deserializer};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
 use std::{env, fs::File, io::Read};
 
 #[derive(Debug, Deserialize, Clone)]
@@ -7,13 +11,24 @@ pub struct Config {
     pub routing: RoutingConfig,
     pub rpc: RpcConfig,
     pub spam: Option<SpamConfig>,
+    pub blockhash: Option<BlockhashConfig>,
+    pub tpu: Option<TpuConfig>,
     pub wallet: WalletConfig,
     pub flashloan: Option<FlashloanConfig>,
+    pub wsol: Option<WsolConfig>,
+    pub risk: Option<RiskConfig>,
+    pub lookup_table: Option<LookupTableConfig>,
+    pub cycles: Option<CycleConfig>,
+    pub nonce: Option<NonceConfig>,
+    pub base_assets: Option<Vec<BaseAssetConfig>>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct BotConfig {
     pub compute_unit_limit: u32,
+    pub ledger_path: Option<String>,
+    pub reload_interval: Option<u64>,
+    pub max_account_locks: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -21,7 +36,7 @@ pub struct RoutingConfig {
     pub mint_config_list: Vec<MintConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct MintConfig {
     pub mint: String,
 
@@ -45,12 +60,28 @@ pub struct MintConfig {
 
     pub lookup_table_accounts: Option<Vec<String>>,
     pub process_delay: u64,
+    pub routes: Option<Vec<RouteConfig>>,
+    /// Set to false to send this mint only through RPC while `[tpu]` is enabled.
+    pub use_tpu: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct RouteConfig {
+    pub name: String,
+    pub pools: Vec<String>,
+    pub minimum_profit: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct RpcConfig {
     #[serde(deserialize_with = "serde_string_or_env")]
     pub url: String,
+    /// More endpoints reads fail over to.
+    #[serde(default, deserialize_with = "serde_strings_or_env")]
+    pub read_urls: Option<Vec<String>>,
+    pub health_check_interval: Option<u64>,
+    pub max_slot_lag: Option<u64>,
+    pub metrics_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -59,12 +90,47 @@ pub struct SpamConfig {
     pub sending_rpc_urls: Vec<String>,
     pub compute_unit_price: u64,
     pub max_retries: Option<u64>,
+    pub rate_limit: Option<f64>,
+    pub rate_limit_burst: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BlockhashConfig {
+    pub refresh_slots: Option<u64>,
+    pub poll_interval: Option<u64>,
+    pub max_age: Option<u64>,
+    pub fallback_rpc_urls: Option<Vec<String>>,
+    pub metrics_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TpuConfig {
+    pub enabled: bool,
+    pub leader_count: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct WalletConfig {
     #[serde(deserialize_with = "serde_string_or_env")]
     pub private_key: String,
+    #[serde(default, deserialize_with = "serde_strings_or_env")]
+    pub extra_private_keys: Option<Vec<String>>,
+    pub rotation: Option<WalletRotation>,
+    #[serde(default, deserialize_with = "serde_option_string_or_env")]
+    pub fee_payer_private_key: Option<String>,
+    #[serde(default, deserialize_with = "serde_strings_or_env")]
+    pub fee_payer_private_keys: Option<Vec<String>>,
+}
+
+/// How mints are given wallets when several are configured.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletRotation {
+    /// Every send takes the next wallet.
+    #[default]
+    PerSend,
+    /// Every mint keeps one wallet.
+    PerMint,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -72,17 +138,252 @@ pub struct FlashloanConfig {
     pub enabled: bool,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct WsolConfig {
+    pub enabled: bool,
+    pub min_balance: u64,
+    pub max_balance: u64,
+    pub native_reserve: u64,
+    pub check_interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RiskConfig {
+    pub enabled: bool,
+    pub max_net_loss: u64,
+    pub loss_window_secs: Option<u64>,
+    pub min_wallet_balance: u64,
+    pub kill_switch_file: Option<String>,
+    pub balance_check_interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LookupTableConfig {
+    pub enabled: bool,
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CycleConfig {
+    pub enabled: bool,
+    pub input_amount: u64,
+    pub min_profit: u64,
+    pub scan_interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct NonceConfig {
+    pub enabled: bool,
+    pub count: usize,
+    pub refresh_interval: Option<u64>,
+    pub reuse_after: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BaseAssetConfig {
+    pub mint: String,
+    pub bridge_pool: String,
+    pub bridge_vault: String,
+    pub bridge_sol_vault: String,
+    pub flashloan_vault: Option<FlashloanVaultConfig>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct FlashloanVaultConfig {
+    pub authority: String,
+    pub token_account: String,
+}
+
 pub fn serde_string_or_env<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
 {
     let value_or_env = String::deserialize(deserializer)?;
-    let value = match value_or_env.chars().next() {
-        Some('$') => env::var(&value_or_env[1..])
-            .unwrap_or_else(|_| panic!("reading `{}` from env", &value_or_env[1..])),
-        _ => value_or_env,
-    };
-    Ok(value)
+    Ok(string_or_env(value_or_env))
+}
+
+pub fn serde_option_string_or_env<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.map(string_or_env))
+}
+
+pub fn serde_strings_or_env<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = Option::<Vec<String>>::deserialize(deserializer)?;
+    Ok(values.map(|values| values.into_iter().map(string_or_env).collect()))
+}
+
+/// Reads `$NAME` values from the environment. Values naming an unset variable are kept as they
+/// are, for `Config::validate` to report.
+fn string_or_env(value_or_env: String) -> String {
+    match value_or_env.strip_prefix('$') {
+        Some(name) => env::var(name).unwrap_or(value_or_env),
+        None => value_or_env,
+    }
+}
+
+/// A problem found in the config, with the TOML path of the value it is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+#[derive(Default)]
+struct Problems(Vec<ConfigProblem>);
+
+impl Problems {
+    fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(ConfigProblem {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    fn pubkey(&mut self, path: impl Into<String>, value: &str) {
+        if value.is_empty() {
+            self.push(path, "is empty");
+        } else if let Err(e) = Pubkey::from_str(value) {
+            self.push(path, format!("`{}` is not a valid address: {}", value, e));
+        }
+    }
+
+    /// Flags values still naming an environment variable, which happens when it is unset.
+    fn env(&mut self, path: impl Into<String>, value: &str) {
+        if value.is_empty() {
+            self.push(path, "is empty");
+        } else if let Some(name) = value.strip_prefix('$') {
+            if env::var(name).is_err() {
+                self.push(path, format!("environment variable `{}` is not set", name));
+            }
+        }
+    }
+}
+
+impl MintConfig {
+    pub fn pool_lists(&self) -> [(&'static str, Option<&Vec<String>>); 11] {
+        [
+            ("raydium_pool_list", self.raydium_pool_list.as_ref()),
+            ("raydium_cp_pool_list", self.raydium_cp_pool_list.as_ref()),
+            (
+                "raydium_clmm_pool_list",
+                self.raydium_clmm_pool_list.as_ref(),
+            ),
+            (
+                "meteora_dlmm_pool_list",
+                self.meteora_dlmm_pool_list.as_ref(),
+            ),
+            (
+                "meteora_damm_pool_list",
+                self.meteora_damm_pool_list.as_ref(),
+            ),
+            (
+                "meteora_damm_v2_pool_list",
+                self.meteora_damm_v2_pool_list.as_ref(),
+            ),
+            ("pump_pool_list", self.pump_pool_list.as_ref()),
+            ("whirlpool_pool_list", self.whirlpool_pool_list.as_ref()),
+            ("solfi_pool_list", self.solfi_pool_list.as_ref()),
+            ("vertigo_pool_list", self.vertigo_pool_list.as_ref()),
+            ("heaven_pool_list", self.heaven_pool_list.as_ref()),
+        ]
+    }
+}
+
+impl RoutingConfig {
+    /// Checks that mints, pools and lookup tables are valid addresses, that no mint or pool is
+    /// listed twice, that every mint has at least two pools and that routes only use listed
+    /// pools.
+    fn check(&self, problems: &mut Problems) {
+        let mut mints: HashMap<&str, String> = HashMap::new();
+        let mut pools: HashMap<&str, String> = HashMap::new();
+        for (i, mint_config) in self.mint_config_list.iter().enumerate() {
+            let path = format!("routing.mint_config_list[{}]", i);
+            problems.pubkey(format!("{}.mint", path), &mint_config.mint);
+            if !mint_config.mint.is_empty() {
+                if let Some(first) = mints.insert(&mint_config.mint, path.clone()) {
+                    problems.push(
+                        format!("{}.mint", path),
+                        format!("mint {} is already listed at {}", mint_config.mint, first),
+                    );
+                }
+            }
+            if mint_config.process_delay == 0 {
+                problems.push(
+                    format!("{}.process_delay", path),
+                    "must be at least 1 millisecond",
+                );
+            }
+
+            for (list_name, addresses) in mint_config.pool_lists() {
+                for (j, pool) in addresses.into_iter().flatten().enumerate() {
+                    let pool_path = format!("{}.{}[{}]", path, list_name, j);
+                    problems.pubkey(&pool_path, pool);
+                    if let Some(first) = pools.get(pool.as_str()) {
+                        problems.push(
+                            &pool_path,
+                            format!("pool {} is already listed at {}", pool, first),
+                        );
+                    } else {
+                        pools.insert(pool, pool_path);
+                    }
+                }
+            }
+            for (j, address) in mint_config
+                .lookup_table_accounts
+                .iter()
+                .flatten()
+                .enumerate()
+            {
+                problems.pubkey(format!("{}.lookup_table_accounts[{}]", path, j), address);
+            }
+
+            let mint_pools: HashSet<&String> = mint_config
+                .pool_lists()
+                .into_iter()
+                .flat_map(|(_, addresses)| addresses.into_iter().flatten())
+                .collect();
+            if mint_pools.len() < 2 {
+                problems.push(
+                    &path,
+                    format!(
+                        "mint {} needs at least two pools to arbitrage between, found {}",
+                        mint_config.mint,
+                        mint_pools.len()
+                    ),
+                );
+            }
+            for (j, route) in mint_config.routes.iter().flatten().enumerate() {
+                let route_path = format!("{}.routes[{}]", path, j);
+                if route.pools.len() < 2 {
+                    problems.push(
+                        format!("{}.pools", route_path),
+                        format!("route {} needs at least two pools", route.name),
+                    );
+                }
+                for pool in route.pools.iter().filter(|p| !mint_pools.contains(p)) {
+                    problems.push(
+                        format!("{}.pools", route_path),
+                        format!(
+                            "route {} uses pool {} which is not in any of the mint's pool lists",
+                            route.name, pool
+                        ),
+                    );
+                }
+            }
+        }
+    }
 }
 
 impl Config {
@@ -92,6 +393,125 @@ impl Config {
         file.read_to_string(&mut contents)?;
 
         let config: Config = toml::from_str(&contents)?;
+        config.validate()?;
         Ok(config)
     }
+
+    /// Every problem found in the config.
+    pub fn problems(&self) -> Vec<ConfigProblem> {
+        let mut problems = Problems::default();
+        problems.env("rpc.url", &self.rpc.url);
+        for (i, url) in self.rpc.read_urls.iter().flatten().enumerate() {
+            problems.env(format!("rpc.read_urls[{}]", i), url);
+        }
+
+        if let Some(spam) = self.spam.as_ref().filter(|s| s.enabled) {
+            if spam.sending_rpc_urls.is_empty() {
+                problems.push(
+                    "spam.sending_rpc_urls",
+                    "spam is enabled but no sending RPC URLs are configured",
+                );
+            }
+            if spam.rate_limit.is_some_and(|rate| rate <= 0.0) {
+                problems.push("spam.rate_limit", "must be above 0");
+            }
+        }
+
+        problems.env("wallet.private_key", &self.wallet.private_key);
+        for (i, key) in self.wallet.extra_private_keys.iter().flatten().enumerate() {
+            problems.env(format!("wallet.extra_private_keys[{}]", i), key);
+        }
+        let wallet_count = 1 + self.wallet.extra_private_keys.as_ref().map_or(0, Vec::len);
+        if let Some(key) = &self.wallet.fee_payer_private_key {
+            problems.env("wallet.fee_payer_private_key", key);
+            if self.wallet.fee_payer_private_keys.is_some() {
+                problems.push(
+                    "wallet.fee_payer_private_key",
+                    "set either fee_payer_private_key or fee_payer_private_keys",
+                );
+            } else if wallet_count > 1 {
+                problems.push(
+                    "wallet.fee_payer_private_key",
+                    "a fee payer shared by several wallets makes their transactions run one after \
+                     another, give each wallet its own in fee_payer_private_keys",
+                );
+            }
+        }
+        if let Some(keys) = &self.wallet.fee_payer_private_keys {
+            for (i, key) in keys.iter().enumerate() {
+                problems.env(format!("wallet.fee_payer_private_keys[{}]", i), key);
+            }
+            if keys.len() != wallet_count {
+                problems.push(
+                    "wallet.fee_payer_private_keys",
+                    format!(
+                        "{} fee payers for {} wallets, list one per wallet in wallet order",
+                        keys.len(),
+                        wallet_count
+                    ),
+                );
+            }
+        }
+
+        if let Some(nonce) = self.nonce.as_ref().filter(|n| n.enabled) {
+            if nonce.count == 0 {
+                problems.push("nonce.count", "must be at least 1");
+            }
+            if wallet_count > 1 {
+                problems.push(
+                    "nonce.enabled",
+                    "durable nonce mode signs with a single wallet, remove wallet.extra_private_keys",
+                );
+            }
+        }
+        if let Some(wsol) = self.wsol.as_ref().filter(|w| w.enabled) {
+            if wsol.min_balance > wsol.max_balance {
+                problems.push(
+                    "wsol.min_balance",
+                    "must not be greater than wsol.max_balance",
+                );
+            }
+        }
+
+        if let Some(address) = self.lookup_table.as_ref().and_then(|l| l.address.as_ref()) {
+            problems.pubkey("lookup_table.address", address);
+        }
+        for (i, asset) in self.base_assets.iter().flatten().enumerate() {
+            let path = format!("base_assets[{}]", i);
+            problems.pubkey(format!("{}.mint", path), &asset.mint);
+            problems.pubkey(format!("{}.bridge_pool", path), &asset.bridge_pool);
+            problems.pubkey(format!("{}.bridge_vault", path), &asset.bridge_vault);
+            problems.pubkey(
+                format!("{}.bridge_sol_vault", path),
+                &asset.bridge_sol_vault,
+            );
+            if let Some(vault) = &asset.flashloan_vault {
+                problems.pubkey(
+                    format!("{}.flashloan_vault.authority", path),
+                    &vault.authority,
+                );
+                problems.pubkey(
+                    format!("{}.flashloan_vault.token_account", path),
+                    &vault.token_account,
+                );
+            }
+        }
+
+        self.routing.check(&mut problems);
+        problems.0
+    }
+
+    /// Fails with every problem found in the config, one per line.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let lines: Vec<String> = problems.iter().map(|p| format!("  {}", p)).collect();
+        anyhow::bail!(
+            "Found {} problem(s) in the config:\n{}",
+            problems.len(),
+            lines.join("\n")
+        )
+    }
 }
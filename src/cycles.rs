@@ -0,0 +1,140 @@
+use crate::config::CycleConfig;
+use crate::pools::PoolRef;
+use crate::quote::{constant_product_fee_bps, constant_product_out};
+use crate::refresh::token_account_amount;
+use crate::rpc::AccountSource;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+
+/// One direction of a pool in the route graph.
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub pool: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub reserve_in: u64,
+    pub reserve_out: u64,
+    pub fee_bps: u64,
+}
+
+impl Edge {
+    pub fn quote(&self, amount_in: u64) -> u64 {
+        constant_product_out(amount_in, self.reserve_in, self.reserve_out, self.fee_bps)
+    }
+}
+
+/// Mints as nodes and quotable pools as edges, across every configured mint.
+#[derive(Debug, Default)]
+pub struct RouteGraph {
+    edges: HashMap<Pubkey, Vec<Edge>>,
+}
+
+impl RouteGraph {
+    pub fn add_pool(&mut self, pool: &PoolRef, token_reserve: u64, base_reserve: u64) {
+        let Some(fee_bps) = constant_product_fee_bps(pool.dex) else {
+            return;
+        };
+        let directions = [
+            (pool.base_mint, pool.token_mint, base_reserve, token_reserve),
+            (pool.token_mint, pool.base_mint, token_reserve, base_reserve),
+        ];
+        for (from, to, reserve_in, reserve_out) in directions {
+            self.edges.entry(from).or_default().push(Edge {
+                pool: pool.pool,
+                from,
+                to,
+                reserve_in,
+                reserve_out,
+                fee_bps,
+            });
+        }
+    }
+
+    fn edges_from(&self, mint: &Pubkey) -> &[Edge] {
+        self.edges.get(mint).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Every `start -> a -> b -> start` cycle through three different pools.
+    pub fn triangular_cycles(&self, start: &Pubkey) -> Vec<[&Edge; 3]> {
+        let mut cycles = Vec::new();
+        for first in self.edges_from(start) {
+            for second in self.edges_from(&first.to) {
+                if second.to == *start || second.pool == first.pool {
+                    continue;
+                }
+                for third in self.edges_from(&second.to) {
+                    if third.to == *start && third.pool != first.pool && third.pool != second.pool {
+                        cycles.push([first, second, third]);
+                    }
+                }
+            }
+        }
+        cycles
+    }
+}
+
+#[derive(Debug)]
+pub struct CycleOpportunity {
+    pub mints: Vec<Pubkey>,
+    pub pools: Vec<Pubkey>,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+impl CycleOpportunity {
+    pub fn profit(&self) -> i64 {
+        self.amount_out as i64 - self.amount_in as i64
+    }
+}
+
+pub fn quote_cycle(cycle: &[&Edge], amount_in: u64) -> u64 {
+    cycle
+        .iter()
+        .fold(amount_in, |amount, edge| edge.quote(amount))
+}
+
+/// Reads the vault balances of every quotable pool and returns the cycles
+/// from `start` whose quoted profit reaches `config.min_profit`.
+pub fn scan_cycles(
+    rpc_client: &dyn AccountSource,
+    pools: &[PoolRef],
+    start: &Pubkey,
+    config: &CycleConfig,
+) -> anyhow::Result<Vec<CycleOpportunity>> {
+    let mut seen = HashSet::new();
+    let quotable: Vec<&PoolRef> = pools
+        .iter()
+        .filter(|p| constant_product_fee_bps(p.dex).is_some() && seen.insert(p.pool))
+        .collect();
+
+    let mut graph = RouteGraph::default();
+    // Two vaults per pool, within the 100 account limit of getMultipleAccounts
+    for chunk in quotable.chunks(50) {
+        let vaults: Vec<Pubkey> = chunk
+            .iter()
+            .flat_map(|p| [p.token_vault, p.base_vault])
+            .collect();
+        let accounts = rpc_client.get_multiple_accounts(&vaults)?;
+        for (pool, balances) in chunk.iter().zip(accounts.chunks(2)) {
+            let token_reserve = balances[0].as_ref().and_then(token_account_amount);
+            let base_reserve = balances[1].as_ref().and_then(token_account_amount);
+            if let (Some(token_reserve), Some(base_reserve)) = (token_reserve, base_reserve) {
+                graph.add_pool(pool, token_reserve, base_reserve);
+            }
+        }
+    }
+
+    let mut opportunities: Vec<CycleOpportunity> = graph
+        .triangular_cycles(start)
+        .into_iter()
+        .map(|cycle| CycleOpportunity {
+            mints: cycle.iter().map(|e| e.from).chain([*start]).collect(),
+            pools: cycle.iter().map(|e| e.pool).collect(),
+            amount_in: config.input_amount,
+            amount_out: quote_cycle(&cycle, config.input_amount),
+        })
+        .filter(|o| o.profit() >= config.min_profit as i64)
+        .collect();
+    opportunities.sort_by_key(|o| std::cmp::Reverse(o.profit()));
+    Ok(opportunities)
+}
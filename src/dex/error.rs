@@ -0,0 +1,85 @@
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+
+/// Why a pool account could not be decoded.
+#[derive(Debug, Error, PartialEq)]
+pub enum DecodeError {
+    #[error("{account} is owned by {actual}, expected {expected}")]
+    WrongOwner {
+        account: Pubkey,
+        expected: Pubkey,
+        actual: Pubkey,
+    },
+    #[error("{layout} discriminator is {actual:?}, expected {expected:?}")]
+    WrongDiscriminator {
+        layout: &'static str,
+        expected: [u8; 8],
+        actual: Vec<u8>,
+    },
+    #[error("{layout} needs {expected} bytes, account has {actual}")]
+    TooShort {
+        layout: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("mint {mint} is not in pool {pool}")]
+    MintMismatch { mint: Pubkey, pool: Pubkey },
+    /// Borsh rejected the bytes, e.g. an invalid enum or bool tag.
+    #[error("{layout} is malformed: {reason}")]
+    Malformed {
+        layout: &'static str,
+        reason: String,
+    },
+}
+
+pub fn check_owner(account: &Pubkey, owner: &Pubkey, expected: &Pubkey) -> Result<(), DecodeError> {
+    if owner != expected {
+        return Err(DecodeError::WrongOwner {
+            account: *account,
+            expected: *expected,
+            actual: *owner,
+        });
+    }
+    Ok(())
+}
+
+pub fn check_len(layout: &'static str, data: &[u8], expected: usize) -> Result<(), DecodeError> {
+    if data.len() < expected {
+        return Err(DecodeError::TooShort {
+            layout,
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks the 8 byte Anchor discriminator and that `data` holds at least `len` bytes, discriminator included.
+pub fn check_anchor_account(
+    layout: &'static str,
+    data: &[u8],
+    discriminator: &[u8; 8],
+    len: usize,
+) -> Result<(), DecodeError> {
+    check_len(layout, data, 8)?;
+    if &data[..8] != discriminator {
+        return Err(DecodeError::WrongDiscriminator {
+            layout,
+            expected: *discriminator,
+            actual: data[..8].to_vec(),
+        });
+    }
+    check_len(layout, data, len)
+}
+
+/// Reads a pubkey at `offset`. The caller has checked the length.
+pub fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&data[offset..offset + 32]);
+    Pubkey::new_from_array(bytes)
+}
+
+/// sha256("account:Pool")[..8], shared by every Anchor program whose pool account is named `Pool`.
+pub const ANCHOR_POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+/// sha256("account:PoolState")[..8], used by Raydium CP and CLMM.
+pub const ANCHOR_POOL_STATE_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
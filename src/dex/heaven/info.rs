@@ -1,3 +1,4 @@
+use crate::dex::error::{check_anchor_account, read_pubkey, DecodeError};
 use solana_program::pubkey::Pubkey;
 
 #[derive(Debug, Clone)]
@@ -15,15 +16,8 @@ impl HeavenPoolState {
     pub const DISCRIMINATOR: [u8; 8] = [190, 158, 220, 130, 15, 162, 132, 252];
     pub const SIZE: usize = 2304;
 
-    pub fn parse(data: &[u8]) -> Option<Self> {
-        if data.len() != Self::SIZE {
-            return None;
-        }
-
-        // Check discriminator
-        if &data[0..8] != &Self::DISCRIMINATOR {
-            return None;
-        }
+    pub fn parse(data: &[u8]) -> Result<Self, DecodeError> {
+        check_anchor_account("Heaven pool", data, &Self::DISCRIMINATOR, Self::SIZE)?;
 
         // Offsets based on Heaven pool structure (copied from reference)
         const OFFSET_RESERVE: usize = 8 + 88 + 360;
@@ -32,22 +26,22 @@ impl HeavenPoolState {
         const OFFSET_TOKEN_A: usize = OFFSET_VAULTS + 128;
         const OFFSET_TOKEN_B: usize = OFFSET_TOKEN_A + 65;
 
-        let reserve_a = u64::from_le_bytes(data[OFFSET_RESERVE..OFFSET_RESERVE + 8].try_into().ok()?);
-        let reserve_b = u64::from_le_bytes(
-            data[OFFSET_RESERVE + 8..OFFSET_RESERVE + 16]
-                .try_into()
-                .ok()?,
-        );
+        let read_u64 = |offset: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let reserve_a = read_u64(OFFSET_RESERVE);
+        let reserve_b = read_u64(OFFSET_RESERVE + 8);
 
-        let vault_a = Pubkey::try_from(&data[OFFSET_VAULTS..OFFSET_VAULTS + 32]).ok()?;
-        let vault_b = Pubkey::try_from(&data[OFFSET_VAULTS + 32..OFFSET_VAULTS + 64]).ok()?;
-        let protocol_config =
-            Pubkey::try_from(&data[OFFSET_PROTOCOL_CONFIG..OFFSET_PROTOCOL_CONFIG + 32]).ok()?;
+        let vault_a = read_pubkey(data, OFFSET_VAULTS);
+        let vault_b = read_pubkey(data, OFFSET_VAULTS + 32);
+        let protocol_config = read_pubkey(data, OFFSET_PROTOCOL_CONFIG);
 
-        let mint_a = Pubkey::try_from(&data[OFFSET_TOKEN_A..OFFSET_TOKEN_A + 32]).ok()?;
-        let mint_b = Pubkey::try_from(&data[OFFSET_TOKEN_B..OFFSET_TOKEN_B + 32]).ok()?;
+        let mint_a = read_pubkey(data, OFFSET_TOKEN_A);
+        let mint_b = read_pubkey(data, OFFSET_TOKEN_B);
 
-        Some(Self {
+        Ok(Self {
             mint_a,
             mint_b,
             vault_a,
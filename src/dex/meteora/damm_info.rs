@@ -0,0 +1,11 @@
+use crate::dex::error::{check_anchor_account, DecodeError, ANCHOR_POOL_DISCRIMINATOR};
+use meteora_damm_cpi::Pool;
+
+/// Decodes a Meteora DAMM pool after checking its discriminator.
+pub fn load_damm_pool(data: &[u8]) -> Result<Pool, DecodeError> {
+    check_anchor_account("Meteora DAMM Pool", data, &ANCHOR_POOL_DISCRIMINATOR, 8)?;
+    Pool::deserialize_unchecked(data).map_err(|e| DecodeError::Malformed {
+        layout: "Meteora DAMM Pool",
+        reason: e.to_string(),
+    })
+}
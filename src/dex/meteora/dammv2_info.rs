@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::dex::error::{check_anchor_account, read_pubkey, DecodeError, ANCHOR_POOL_DISCRIMINATOR};
 use solana_sdk::pubkey::Pubkey;
 
 pub struct MeteoraDAmmV2Info {
@@ -9,11 +9,13 @@ pub struct MeteoraDAmmV2Info {
 }
 
 impl MeteoraDAmmV2Info {
-    pub fn load_checked(data: &[u8]) -> Result<Self> {
-        let base_mint = Pubkey::new(&data[168..200]);
-        let quote_mint = Pubkey::new(&data[200..232]);
-        let base_vault = Pubkey::new(&data[232..264]);
-        let quote_vault = Pubkey::new(&data[264..296]);
+    pub fn load_checked(data: &[u8]) -> Result<Self, DecodeError> {
+        check_anchor_account("MeteoraDAmmV2Info", data, &ANCHOR_POOL_DISCRIMINATOR, 296)?;
+
+        let base_mint = read_pubkey(data, 168);
+        let quote_mint = read_pubkey(data, 200);
+        let base_vault = read_pubkey(data, 232);
+        let quote_vault = read_pubkey(data, 264);
         Ok(Self {
             base_mint,
             quote_mint,
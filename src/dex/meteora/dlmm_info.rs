@@ -1,3 +1,4 @@
+use crate::dex::error::{check_anchor_account, DecodeError};
 use crate::dex::meteora::constants::{dlmm_program_id, BIN_ARRAY};
 use anyhow::Result;
 use solana_program::pubkey::Pubkey;
@@ -97,11 +98,17 @@ pub struct DlmmInfo {
     pub lb_pair: LbPair,
 }
 
+/// sha256("account:LbPair")[..8]
+const LB_PAIR_DISCRIMINATOR: [u8; 8] = [33, 11, 49, 98, 181, 101, 177, 13];
+
 impl DlmmInfo {
-    pub fn load_checked(data: &[u8]) -> Result<Self> {
-        if data.len() < 8 + size_of::<LbPair>() {
-            return Err(anyhow::anyhow!("Invalid data length for DlmmInfo"));
-        }
+    pub fn load_checked(data: &[u8]) -> Result<Self, DecodeError> {
+        check_anchor_account(
+            "DlmmInfo",
+            data,
+            &LB_PAIR_DISCRIMINATOR,
+            8 + size_of::<LbPair>(),
+        )?;
 
         let raw_lb_pair = &data[8..8 + size_of::<LbPair>()];
 
@@ -121,29 +128,6 @@ impl DlmmInfo {
         })
     }
 
-    pub fn get_token_and_sol_vaults(&self, mint: &Pubkey, sol_mint: &Pubkey) -> (Pubkey, Pubkey) {
-        let token_vault;
-        let sol_vault;
-
-        if sol_mint == &self.token_x_mint {
-            sol_vault = self.token_x_vault;
-            token_vault = self.token_y_vault;
-        } else if sol_mint == &self.token_y_mint {
-            sol_vault = self.token_y_vault;
-            token_vault = self.token_x_vault;
-        } else {
-            if mint == &self.token_x_mint {
-                token_vault = self.token_x_vault;
-                sol_vault = self.token_y_vault;
-            } else {
-                token_vault = self.token_y_vault;
-                sol_vault = self.token_x_vault;
-            }
-        }
-
-        (token_vault, sol_vault)
-    }
-
     pub fn calculate_bin_arrays(&self, pair_pubkey: &Pubkey) -> Result<Vec<Pubkey>> {
         let bin_array_index = self.bin_id_to_bin_array_index(self.active_id)?;
 
@@ -1,3 +1,4 @@
+pub mod error;
 pub mod heaven;
 pub mod meteora;
 pub mod pump;
@@ -5,3 +6,5 @@ pub mod raydium;
 pub mod solfi;
 pub mod vertigo;
 pub mod whirlpool;
+
+pub use error::DecodeError;
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::dex::error::{check_anchor_account, read_pubkey, DecodeError, ANCHOR_POOL_DISCRIMINATOR};
 use solana_program::pubkey::Pubkey;
 
 use super::constants::pump_program_id;
@@ -17,26 +17,20 @@ pub struct PumpAmmInfo {
 }
 
 impl PumpAmmInfo {
-    pub fn load_checked(data: &[u8]) -> Result<Self> {
-        let data = &data[8..];
+    pub fn load_checked(data: &[u8]) -> Result<Self, DecodeError> {
         let base_mint_offset = 1 + 2 + 32; // bump + index + creator
         let quote_mint_offset = base_mint_offset + 32;
         let pool_base_offset = quote_mint_offset + 32 + 32; // + lp mint
         let pool_quote_offset = pool_base_offset + 32;
         let min_len = pool_quote_offset + 32;
 
-        if data.len() < min_len {
-            return Err(anyhow::anyhow!("Invalid data length for PumpAmmInfo"));
-        }
+        check_anchor_account("PumpAmmInfo", data, &ANCHOR_POOL_DISCRIMINATOR, 8 + min_len)?;
+        let data = &data[8..];
 
-        let base_mint =
-            Pubkey::new(&data[base_mint_offset..base_mint_offset + 32]);
-        let quote_mint =
-            Pubkey::new(&data[quote_mint_offset..quote_mint_offset + 32]);
-        let pool_base_token_account =
-            Pubkey::new(&data[pool_base_offset..pool_base_offset + 32]);
-        let pool_quote_token_account =
-            Pubkey::new(&data[pool_quote_offset..pool_quote_offset + 32]);
+        let base_mint = read_pubkey(data, base_mint_offset);
+        let quote_mint = read_pubkey(data, quote_mint_offset);
+        let pool_base_token_account = read_pubkey(data, pool_base_offset);
+        let pool_quote_token_account = read_pubkey(data, pool_quote_offset);
 
         let coin_creator_offset = pool_quote_offset + 8 + 32; // lp_supply + last_trade_timestamp
         let is_mayhem_mode_offset = coin_creator_offset + 32;
@@ -44,7 +38,7 @@ impl PumpAmmInfo {
         let coin_creator = if coin_creator_offset + 32 > data.len() {
             Pubkey::default()
         } else {
-            Pubkey::new(&data[coin_creator_offset..coin_creator_offset + 32])
+            read_pubkey(data, coin_creator_offset)
         };
 
         let is_mayhem_mode = if is_mayhem_mode_offset >= data.len() {
@@ -1,5 +1,5 @@
+use crate::dex::error::{check_len, read_pubkey, DecodeError};
 use solana_program::pubkey::Pubkey;
-use anyhow::Result;
 
 const COIN_VAULT_OFFSET: usize = 336; // coinVault/tokenVaultA
 const PC_VAULT_OFFSET: usize = 368; // pcVault/tokenVaultB
@@ -15,15 +15,14 @@ pub struct RaydiumAmmInfo {
 }
 
 impl RaydiumAmmInfo {
-    pub fn load_checked(data: &[u8]) -> Result<Self> {
-        if data.len() < PC_MINT_OFFSET + 32 {
-            return Err(anyhow::anyhow!("Invalid data length for RaydiumAmmInfo"));
-        }
-        
-        let coin_vault = Pubkey::new(&data[COIN_VAULT_OFFSET..COIN_VAULT_OFFSET + 32]);
-        let pc_vault = Pubkey::new(&data[PC_VAULT_OFFSET..PC_VAULT_OFFSET + 32]);
-        let coin_mint = Pubkey::new(&data[COIN_MINT_OFFSET..COIN_MINT_OFFSET + 32]);
-        let pc_mint = Pubkey::new(&data[PC_MINT_OFFSET..PC_MINT_OFFSET + 32]);
+    /// AMM v4 is not an Anchor program, so there is no discriminator to check.
+    pub fn load_checked(data: &[u8]) -> Result<Self, DecodeError> {
+        check_len("RaydiumAmmInfo", data, PC_MINT_OFFSET + 32)?;
+
+        let coin_vault = read_pubkey(data, COIN_VAULT_OFFSET);
+        let pc_vault = read_pubkey(data, PC_VAULT_OFFSET);
+        let coin_mint = read_pubkey(data, COIN_MINT_OFFSET);
+        let pc_mint = read_pubkey(data, PC_MINT_OFFSET);
         
         Ok(Self {
             coin_mint,
@@ -1,3 +1,4 @@
+use crate::dex::error::{check_anchor_account, DecodeError, ANCHOR_POOL_STATE_DISCRIMINATOR};
 use anyhow::Result;
 use solana_program::pubkey::Pubkey;
 
@@ -8,6 +9,9 @@ pub const REWARD_NUM: usize = 3;
 
 pub const POOL_TICK_ARRAY_BITMAP_SEED: &str = "pool_tick_array_bitmap_extension";
 
+/// Discriminator plus every field up to and including `tick_current`.
+const POOL_STATE_DECODED_LEN: usize = 8 + 1 + 32 * 7 + 2 + 2 + 16 + 16 + 4;
+
 pub enum RewardState {
     Uninitialized,
     Initialized,
@@ -102,12 +106,13 @@ pub struct PoolState {
 }
 
 impl PoolState {
-    pub fn load_checked(data: &[u8]) -> Result<Self> {
-        if data.len() < 8 + 32 + 32 + 32 + 32 + 32 + 32 + 4 + 2 {
-            return Err(anyhow::anyhow!(
-                "Invalid data length for RaydiumClmmPoolState"
-            ));
-        }
+    pub fn load_checked(data: &[u8]) -> Result<Self, DecodeError> {
+        check_anchor_account(
+            "RaydiumClmmPoolState",
+            data,
+            &ANCHOR_POOL_STATE_DISCRIMINATOR,
+            POOL_STATE_DECODED_LEN,
+        )?;
 
         let data = &data[8..]; // Skip the discriminator
         let mut offset = 0;
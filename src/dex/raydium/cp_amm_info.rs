@@ -1,5 +1,7 @@
+use crate::dex::error::{
+    check_anchor_account, read_pubkey, DecodeError, ANCHOR_POOL_STATE_DISCRIMINATOR,
+};
 use solana_program::pubkey::Pubkey;
-use anyhow::Result;
 
 const AMM_CONFIG_OFFSET: usize = 8; // amm_config
 const POOL_CREATOR_OFFSET: usize = 40; // pool_creator
@@ -23,17 +25,20 @@ pub struct RaydiumCpAmmInfo {
 }
 
 impl RaydiumCpAmmInfo {
-    pub fn load_checked(data: &[u8]) -> Result<Self> {
-        if data.len() < OBSERVATION_KEY_OFFSET + 32 {
-            return Err(anyhow::anyhow!("Invalid data length for RaydiumCpAmmInfo"));
-        }
-        
-        let token_0_vault = Pubkey::new(&data[TOKEN_0_VAULT_OFFSET..TOKEN_0_VAULT_OFFSET + 32]);
-        let token_1_vault = Pubkey::new(&data[TOKEN_1_VAULT_OFFSET..TOKEN_1_VAULT_OFFSET + 32]);
-        let token_0_mint = Pubkey::new(&data[TOKEN_0_MINT_OFFSET..TOKEN_0_MINT_OFFSET + 32]);
-        let token_1_mint = Pubkey::new(&data[TOKEN_1_MINT_OFFSET..TOKEN_1_MINT_OFFSET + 32]);
-        let amm_config = Pubkey::new(&data[AMM_CONFIG_OFFSET..AMM_CONFIG_OFFSET + 32]);
-        let observation_key = Pubkey::new(&data[OBSERVATION_KEY_OFFSET..OBSERVATION_KEY_OFFSET + 32]);
+    pub fn load_checked(data: &[u8]) -> Result<Self, DecodeError> {
+        check_anchor_account(
+            "RaydiumCpAmmInfo",
+            data,
+            &ANCHOR_POOL_STATE_DISCRIMINATOR,
+            OBSERVATION_KEY_OFFSET + 32,
+        )?;
+
+        let token_0_vault = read_pubkey(data, TOKEN_0_VAULT_OFFSET);
+        let token_1_vault = read_pubkey(data, TOKEN_1_VAULT_OFFSET);
+        let token_0_mint = read_pubkey(data, TOKEN_0_MINT_OFFSET);
+        let token_1_mint = read_pubkey(data, TOKEN_1_MINT_OFFSET);
+        let amm_config = read_pubkey(data, AMM_CONFIG_OFFSET);
+        let observation_key = read_pubkey(data, OBSERVATION_KEY_OFFSET);
         
         Ok(Self {
             token_0_mint,
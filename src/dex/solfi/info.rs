@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::dex::error::{check_len, read_pubkey, DecodeError};
 use solana_sdk::pubkey::Pubkey;
 
 pub struct SolfiInfo {
@@ -9,11 +9,14 @@ pub struct SolfiInfo {
 }
 
 impl SolfiInfo {
-    pub fn load_checked(data: &[u8]) -> Result<Self> {
-        let base_mint = Pubkey::new(&data[2664..2696]);
-        let quote_mint = Pubkey::new(&data[2696..2728]);
-        let base_vault = Pubkey::new(&data[2736..2768]);
-        let quote_vault = Pubkey::new(&data[2768..2800]);
+    /// Solfi is not an Anchor program, so only the length is checked.
+    pub fn load_checked(data: &[u8]) -> Result<Self, DecodeError> {
+        check_len("SolfiInfo", data, 2800)?;
+
+        let base_mint = read_pubkey(data, 2664);
+        let quote_mint = read_pubkey(data, 2696);
+        let base_vault = read_pubkey(data, 2736);
+        let quote_vault = read_pubkey(data, 2768);
 
         Ok(Self {
             base_mint,
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::dex::error::{check_anchor_account, DecodeError, ANCHOR_POOL_DISCRIMINATOR};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
@@ -11,9 +11,20 @@ pub struct VertigoPool {
 }
 
 impl VertigoPool {
-    pub fn try_deserialize(data: &mut &[u8]) -> Result<Self> {
-        Self::try_from_slice(data)
-            .map_err(|e| anyhow::anyhow!("Failed to deserialize VertigoPool: {}", e))
+    pub const LEN: usize = 96;
+
+    /// Reads the leading fields after the Anchor discriminator; the rest of the account is ignored.
+    pub fn try_deserialize(data: &[u8]) -> Result<Self, DecodeError> {
+        check_anchor_account(
+            "VertigoPool",
+            data,
+            &ANCHOR_POOL_DISCRIMINATOR,
+            8 + Self::LEN,
+        )?;
+        Self::deserialize(&mut &data[8..]).map_err(|e| DecodeError::Malformed {
+            layout: "VertigoPool",
+            reason: e.to_string(),
+        })
     }
 }
 
@@ -25,9 +36,8 @@ pub struct VertigoInfo {
 }
 
 impl VertigoInfo {
-    pub fn load_checked(data: &[u8], pool: &Pubkey) -> Result<Self> {
-        let mut data_slice = &data[..];
-        let vertigo_pool = VertigoPool::try_deserialize(&mut data_slice)?;
+    pub fn load_checked(data: &[u8], pool: &Pubkey) -> Result<Self, DecodeError> {
+        let vertigo_pool = VertigoPool::try_deserialize(data)?;
 
         Ok(Self {
             mint_a: vertigo_pool.mint_a,
@@ -35,22 +45,6 @@ impl VertigoInfo {
             pool: pool.to_owned(),
         })
     }
-
-    pub fn get_token_and_sol_vaults(&self, base_mint: &str, sol_mint: &Pubkey) -> (Pubkey, Pubkey) {
-        let token_x_vault = if base_mint == self.mint_a.to_string() {
-            derive_vault_address(&self.pool, &self.mint_b).0
-        } else {
-            derive_vault_address(&self.pool, &self.mint_a).0
-        };
-
-        let token_base_vault = if base_mint == self.mint_a.to_string() {
-            derive_vault_address(&self.pool, &self.mint_a).0
-        } else {
-            derive_vault_address(&self.pool, &self.mint_b).0
-        };
-
-        (token_x_vault, token_base_vault)
-    }
 }
 
 /// Helper function to derive vault PDA
@@ -75,13 +75,14 @@ impl Tick {
 
 pub const TICK_ARRAY_SIZE: usize = 88;
 
-use std::io::{Error, ErrorKind, Result};
+use crate::dex::error::{check_anchor_account, DecodeError};
+
+/// sha256("account:Whirlpool")[..8]
+const WHIRLPOOL_DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
 
 impl Whirlpool {
-    pub fn try_deserialize(data: &[u8]) -> Result<Self> {
-        if data.len() < Self::LEN {
-            return Err(Error::new(ErrorKind::InvalidData, "data too short for Whirlpool"));
-        }
+    pub fn try_deserialize(data: &[u8]) -> Result<Self, DecodeError> {
+        check_anchor_account("Whirlpool", data, &WHIRLPOOL_DISCRIMINATOR, Self::LEN)?;
 
         let data = &data[8..];
         
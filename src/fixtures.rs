@@ -0,0 +1,280 @@
+use crate::dex::error::check_owner;
+use crate::dex::heaven::HeavenPoolState;
+use crate::dex::meteora::damm_info::load_damm_pool;
+use crate::dex::meteora::dammv2_info::MeteoraDAmmV2Info;
+use crate::dex::meteora::dlmm_info::DlmmInfo;
+use crate::dex::pump::{pump_fee_wallet, pump_mayhem_fee_wallet, PumpAmmInfo};
+use crate::dex::raydium::clmm_info::POOL_TICK_ARRAY_BITMAP_SEED;
+use crate::dex::raydium::{get_tick_array_pubkeys, PoolState, RaydiumAmmInfo, RaydiumCpAmmInfo};
+use crate::dex::solfi::info::SolfiInfo;
+use crate::dex::vertigo::{derive_vault_address, VertigoInfo};
+use crate::dex::whirlpool::{state::Whirlpool, update_tick_array_accounts_for_onchain};
+use crate::pools::DexKind;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::info;
+
+/// A pool account dumped from mainnet, with the fields decoded from it when it was captured.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountFixture {
+    pub dex: String,
+    pub address: String,
+    pub owner: String,
+    pub slot: u64,
+    /// Account data, base64 encoded.
+    pub data: String,
+    /// Decoded mints, vaults and derived PDAs. Check them against an explorer before committing a fixture.
+    pub expected: BTreeMap<String, String>,
+}
+
+impl AccountFixture {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Writes the fixture to `<dir>/<dex>/<address>.json` and returns the path.
+    pub fn save(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        let dex_dir = dir.join(&self.dex);
+        fs::create_dir_all(&dex_dir)?;
+        let path = dex_dir.join(format!("{}.json", self.address));
+        fs::write(&path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(path)
+    }
+
+    pub fn account_data(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(STANDARD.decode(&self.data)?)
+    }
+}
+
+/// Fetches a pool account and decodes it with the current decoders.
+pub fn capture_fixture(
+    rpc_client: &RpcClient,
+    dex: DexKind,
+    address: &Pubkey,
+) -> anyhow::Result<AccountFixture> {
+    let response = rpc_client.get_account_with_commitment(address, rpc_client.commitment())?;
+    let account = response
+        .value
+        .ok_or_else(|| anyhow::anyhow!("Account {} not found", address))?;
+    check_owner(address, &account.owner, &dex.program_id())?;
+
+    Ok(AccountFixture {
+        dex: dex.name().to_string(),
+        address: address.to_string(),
+        owner: account.owner.to_string(),
+        slot: response.context.slot,
+        data: STANDARD.encode(&account.data),
+        expected: decode_fields(dex, address, &account.data)?,
+    })
+}
+
+/// The mints, vaults and derived accounts the bot reads from a pool account, by name.
+pub fn decode_fields(
+    dex: DexKind,
+    address: &Pubkey,
+    data: &[u8],
+) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut fields = Fields::default();
+    match dex {
+        DexKind::Raydium => {
+            let info = RaydiumAmmInfo::load_checked(data)?;
+            fields.add("coin_mint", info.coin_mint);
+            fields.add("pc_mint", info.pc_mint);
+            fields.add("coin_vault", info.coin_vault);
+            fields.add("pc_vault", info.pc_vault);
+        }
+        DexKind::RaydiumCp => {
+            let info = RaydiumCpAmmInfo::load_checked(data)?;
+            fields.add("token_0_mint", info.token_0_mint);
+            fields.add("token_1_mint", info.token_1_mint);
+            fields.add("token_0_vault", info.token_0_vault);
+            fields.add("token_1_vault", info.token_1_vault);
+            fields.add("amm_config", info.amm_config);
+            fields.add("observation_key", info.observation_key);
+        }
+        DexKind::Pump => {
+            let info = PumpAmmInfo::load_checked(data)?;
+            let fee_wallet = if info.is_mayhem_mode {
+                pump_mayhem_fee_wallet()
+            } else {
+                pump_fee_wallet()
+            };
+            fields.add("base_mint", info.base_mint);
+            fields.add("quote_mint", info.quote_mint);
+            fields.add("pool_base_token_account", info.pool_base_token_account);
+            fields.add("pool_quote_token_account", info.pool_quote_token_account);
+            fields.add(
+                "coin_creator_vault_authority",
+                info.coin_creator_vault_authority,
+            );
+            fields.add(
+                "coin_creator_vault_ata",
+                get_associated_token_address(&info.coin_creator_vault_authority, &info.quote_mint),
+            );
+            fields.add(
+                "fee_token_wallet",
+                get_associated_token_address(&fee_wallet, &info.quote_mint),
+            );
+        }
+        DexKind::Dlmm => {
+            let info = DlmmInfo::load_checked(data)?;
+            fields.add("token_x_mint", info.token_x_mint);
+            fields.add("token_y_mint", info.token_y_mint);
+            fields.add("token_x_vault", info.token_x_vault);
+            fields.add("token_y_vault", info.token_y_vault);
+            fields.add("oracle", info.oracle);
+            fields.add_all("bin_array", info.calculate_bin_arrays(address)?);
+        }
+        DexKind::Whirlpool => {
+            let whirlpool = Whirlpool::try_deserialize(data)?;
+            let program_id = dex.program_id();
+            fields.add("token_mint_a", whirlpool.token_mint_a);
+            fields.add("token_mint_b", whirlpool.token_mint_b);
+            fields.add("token_vault_a", whirlpool.token_vault_a);
+            fields.add("token_vault_b", whirlpool.token_vault_b);
+            fields.add(
+                "oracle",
+                Pubkey::find_program_address(&[b"oracle", address.as_ref()], &program_id).0,
+            );
+            fields.add_all(
+                "tick_array",
+                update_tick_array_accounts_for_onchain(&whirlpool, address, &program_id)
+                    .into_iter()
+                    .map(|meta| meta.pubkey),
+            );
+        }
+        DexKind::RaydiumClmm => {
+            let pool = PoolState::load_checked(data)?;
+            let program_id = dex.program_id();
+            fields.add("token_mint_0", pool.token_mint_0);
+            fields.add("token_mint_1", pool.token_mint_1);
+            fields.add("token_vault_0", pool.token_vault_0);
+            fields.add("token_vault_1", pool.token_vault_1);
+            fields.add("amm_config", pool.amm_config);
+            fields.add("observation_key", pool.observation_key);
+            fields.add(
+                "bitmap_extension",
+                Pubkey::find_program_address(
+                    &[POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(), address.as_ref()],
+                    &program_id,
+                )
+                .0,
+            );
+            fields.add_all(
+                "tick_array",
+                get_tick_array_pubkeys(
+                    address,
+                    pool.tick_current,
+                    pool.tick_spacing,
+                    &[-1, 0, 1],
+                    &program_id,
+                )?,
+            );
+        }
+        DexKind::MeteoraDAmm => {
+            let pool = load_damm_pool(data)?;
+            fields.add("token_a_mint", pool.token_a_mint);
+            fields.add("token_b_mint", pool.token_b_mint);
+            fields.add("a_vault", pool.a_vault);
+            fields.add("b_vault", pool.b_vault);
+            fields.add("a_vault_lp", pool.a_vault_lp);
+            fields.add("b_vault_lp", pool.b_vault_lp);
+            fields.add("admin_token_a_fee", pool.admin_token_a_fee);
+            fields.add("admin_token_b_fee", pool.admin_token_b_fee);
+        }
+        DexKind::Solfi => {
+            let info = SolfiInfo::load_checked(data)?;
+            fields.add("base_mint", info.base_mint);
+            fields.add("quote_mint", info.quote_mint);
+            fields.add("base_vault", info.base_vault);
+            fields.add("quote_vault", info.quote_vault);
+        }
+        DexKind::MeteoraDAmmV2 => {
+            let info = MeteoraDAmmV2Info::load_checked(data)?;
+            fields.add("base_mint", info.base_mint);
+            fields.add("quote_mint", info.quote_mint);
+            fields.add("base_vault", info.base_vault);
+            fields.add("quote_vault", info.quote_vault);
+        }
+        DexKind::Vertigo => {
+            let info = VertigoInfo::load_checked(data, address)?;
+            fields.add("mint_a", info.mint_a);
+            fields.add("mint_b", info.mint_b);
+            fields.add("vault_a", derive_vault_address(address, &info.mint_a).0);
+            fields.add("vault_b", derive_vault_address(address, &info.mint_b).0);
+        }
+        DexKind::Heaven => {
+            let info = HeavenPoolState::parse(data)?;
+            fields.add("mint_a", info.mint_a);
+            fields.add("mint_b", info.mint_b);
+            fields.add("vault_a", info.vault_a);
+            fields.add("vault_b", info.vault_b);
+            fields.add("protocol_config", info.protocol_config);
+        }
+    }
+    Ok(fields.0)
+}
+
+/// Captures `addresses` of `dex` into `<out>/<dex>/<address>.json`. Backs the `capture` command.
+pub fn capture_fixtures(
+    rpc_url: &str,
+    dex: &str,
+    addresses: &[&str],
+    out: &Path,
+) -> anyhow::Result<()> {
+    let dex = DexKind::from_name(dex).ok_or_else(|| {
+        let names: Vec<&str> = DexKind::ALL.iter().map(|d| d.name()).collect();
+        anyhow::anyhow!("Unknown dex {}, expected one of {}", dex, names.join(", "))
+    })?;
+    let rpc_client = RpcClient::new(rpc_url.to_string());
+
+    for address in addresses {
+        let address = Pubkey::from_str(address)
+            .map_err(|e| anyhow::anyhow!("Invalid address {}: {}", address, e))?;
+        let fixture = capture_fixture(&rpc_client, dex, &address)?;
+        let path = fixture.save(out)?;
+
+        // Read the file back the way the tests do
+        let saved = AccountFixture::load(&path)?;
+        if decode_fields(dex, &address, &saved.account_data()?)? != fixture.expected {
+            anyhow::bail!("{} does not decode to the captured fields", path.display());
+        }
+
+        info!(
+            "Captured {} pool {} at slot {}",
+            dex.name(),
+            address,
+            fixture.slot
+        );
+        for (name, value) in &fixture.expected {
+            info!("    {}: {}", name, value);
+        }
+        info!("    Saved to {}", path.display());
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Fields(BTreeMap<String, String>);
+
+impl Fields {
+    fn add(&mut self, name: &str, value: Pubkey) {
+        self.0.insert(name.to_string(), value.to_string());
+    }
+
+    /// Adds a list of accounts as `<name>_0`, `<name>_1`, ...
+    fn add_all(&mut self, name: &str, values: impl IntoIterator<Item = Pubkey>) {
+        for (i, value) in values.into_iter().enumerate() {
+            self.add(&format!("{}_{}", name, i), value);
+        }
+    }
+}
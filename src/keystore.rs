@@ -0,0 +1,197 @@
+use anyhow::Context;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use std::fs;
+use std::path::Path;
+
+/// Environment variable the keystore passphrase is read from. Without it the passphrase is asked
+/// for on the terminal.
+pub const PASSPHRASE_ENV: &str = "WALLET_KEYSTORE_PASSPHRASE";
+const VERSION: u32 = 2;
+const KDF: &str = "argon2id";
+const CIPHER: &str = "aes-256-gcm";
+const SALT_LEN: usize = 16;
+
+/// Cost of deriving the key from the passphrase, stored in the keystore so it can be raised for
+/// new keystores without breaking existing ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB and 3 passes.
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+/// A keypair encrypted with a key derived from a passphrase.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    /// Public key of the encrypted keypair, so the keystore can be told apart without unlocking.
+    pub pubkey: String,
+    pub kdf: String,
+    pub kdf_params: KdfParams,
+    pub cipher: String,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    pub fn encrypt(
+        keypair: &Keypair,
+        passphrase: &str,
+        kdf_params: KdfParams,
+    ) -> anyhow::Result<Self> {
+        let rng = SystemRandom::new();
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut salt)
+            .and_then(|_| rng.fill(&mut nonce))
+            .map_err(|_| anyhow::anyhow!("Failed to generate random bytes"))?;
+
+        let pubkey = keypair.pubkey().to_string();
+        let key = derive_key(passphrase, &salt, kdf_params)?;
+        let mut ciphertext = keypair.to_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(pubkey.as_bytes()),
+            &mut ciphertext,
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt keypair"))?;
+
+        Ok(Self {
+            version: VERSION,
+            pubkey,
+            kdf: KDF.to_string(),
+            kdf_params,
+            cipher: CIPHER.to_string(),
+            salt: STANDARD.encode(salt),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> anyhow::Result<Keypair> {
+        if self.version != VERSION || self.kdf != KDF || self.cipher != CIPHER {
+            anyhow::bail!(
+                "Unsupported keystore version {} ({}, {})",
+                self.version,
+                self.kdf,
+                self.cipher
+            );
+        }
+        let salt = STANDARD.decode(&self.salt)?;
+        let nonce = Nonce::try_assume_unique_for_key(&STANDARD.decode(&self.nonce)?)
+            .map_err(|_| anyhow::anyhow!("Keystore nonce has the wrong length"))?;
+        let key = derive_key(passphrase, &salt, self.kdf_params)?;
+        let mut ciphertext = STANDARD.decode(&self.ciphertext)?;
+        let plaintext = key
+            .open_in_place(nonce, Aad::from(self.pubkey.as_bytes()), &mut ciphertext)
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase, or the keystore is corrupted"))?;
+
+        let keypair = Keypair::from_bytes(plaintext)
+            .map_err(|e| anyhow::anyhow!("Invalid keypair bytes: {}", e))?;
+        if keypair.pubkey().to_string() != self.pubkey {
+            anyhow::bail!("Keystore holds a keypair for another public key");
+        }
+        Ok(keypair)
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Whether `path` is a file in the keystore format, a JSON object. Keypair files hold a JSON
+    /// array. The object's fields are only checked by `read`.
+    pub fn is_keystore(path: &Path) -> bool {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+            .is_some_and(|value| value.is_object())
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], kdf_params: KdfParams) -> anyhow::Result<LessSafeKey> {
+    let mut key = [0u8; 32];
+    let params = Params::new(
+        kdf_params.memory_kib,
+        kdf_params.iterations,
+        kdf_params.parallelism,
+        Some(key.len()),
+    )
+    .map_err(|e| anyhow::anyhow!("Invalid keystore KDF parameters: {}", e))?;
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Failed to derive the keystore key: {}", e))?;
+    let key = UnboundKey::new(&AES_256_GCM, &key)
+        .map_err(|_| anyhow::anyhow!("Failed to create the keystore key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// The passphrase from `WALLET_KEYSTORE_PASSPHRASE`, or else asked for on the terminal.
+pub fn passphrase(prompt: &str) -> anyhow::Result<String> {
+    match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => Ok(rpassword::prompt_password(prompt)?),
+    }
+}
+
+/// Decrypts the keystore at `path`.
+pub fn unlock(path: &Path, keystore: &Keystore) -> anyhow::Result<Keypair> {
+    let passphrase = passphrase(&format!(
+        "Passphrase for keystore {} ({}): ",
+        path.display(),
+        keystore.pubkey
+    ))?;
+    keystore
+        .decrypt(&passphrase)
+        .with_context(|| format!("Failed to unlock keystore {}", path.display()))
+}
+
+/// Encrypts `keypair` into a new keystore file at `out`.
+pub fn create_keystore(keypair: &Keypair, out: &Path) -> anyhow::Result<()> {
+    if out.exists() {
+        anyhow::bail!("{} already exists", out.display());
+    }
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let passphrase = rpassword::prompt_password("New keystore passphrase: ")?;
+            if rpassword::prompt_password("Repeat the passphrase: ")? != passphrase {
+                anyhow::bail!("Passphrases do not match");
+            }
+            passphrase
+        }
+    };
+    if passphrase.is_empty() {
+        anyhow::bail!("The keystore passphrase must not be empty");
+    }
+    Keystore::encrypt(keypair, &passphrase, KdfParams::default())?.write(out)
+}
@@ -0,0 +1,109 @@
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::error;
+
+pub const DEFAULT_LEDGER_PATH: &str = "ledger.jsonl";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LedgerEntry {
+    WsolAccountCreated {
+        wallet: String,
+        account: String,
+        signature: String,
+    },
+    Wrap {
+        wallet: String,
+        amount: u64,
+        wsol_balance_before: u64,
+        signature: String,
+    },
+    Unwrap {
+        wallet: String,
+        amount: u64,
+        wsol_balance_before: u64,
+        signature: String,
+    },
+    TransactionSettled {
+        signature: String,
+        mint: String,
+        wallet: String,
+        success: bool,
+        fee: u64,
+        pnl: i64,
+    },
+    TradingHalted {
+        reason: String,
+    },
+    TradingResumed,
+    LookupTableCreated {
+        authority: String,
+        address: String,
+        signature: String,
+    },
+    LookupTableExtended {
+        address: String,
+        added: usize,
+        signature: String,
+    },
+    NonceAccountCreated {
+        authority: String,
+        address: String,
+        signature: String,
+    },
+}
+
+#[derive(Serialize)]
+struct LedgerRecord<'a> {
+    timestamp_ms: u128,
+    #[serde(flatten)]
+    entry: &'a LedgerEntry,
+}
+
+/// Append-only JSON lines log of every action the bot takes with wallet funds.
+pub struct Ledger {
+    writer: Mutex<LineWriter<File>>,
+}
+
+impl Ledger {
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Mutex::new(LineWriter::new(file)),
+        })
+    }
+
+    pub fn record(&self, entry: LedgerEntry) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let record = LedgerRecord {
+            timestamp_ms,
+            entry: &entry,
+        };
+
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                error!("Failed to serialize ledger entry {:?}: {}", entry, e);
+                return;
+            }
+        };
+
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(writer, "{}", line) {
+            error!("Failed to write ledger entry: {}", e);
+        }
+    }
+
+    pub fn flush(&self) {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writer.flush() {
+            error!("Failed to flush ledger: {}", e);
+        }
+    }
+}
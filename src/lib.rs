@@ -1,7 +1,29 @@
+pub mod backtest;
+pub mod base_assets;
+pub mod blockhash;
 pub mod bot;
 pub mod config;
 pub mod constants;
+pub mod cycles;
 pub mod dex;
+pub mod fixtures;
+pub mod keystore;
+pub mod ledger;
+pub mod lookup_table;
+pub mod nonce;
 pub mod pools;
+pub mod quote;
+pub mod recorder;
 pub mod refresh;
+pub mod reload;
+pub mod remote_signer;
+pub mod risk;
+pub mod rpc;
+pub mod rpc_pool;
+pub mod sender;
+pub mod snapshot;
+pub mod supervisor;
+pub mod tpu;
 pub mod transaction;
+pub mod wallets;
+pub mod wsol;
@@ -0,0 +1,191 @@
+use crate::config::LookupTableConfig;
+use crate::ledger::{Ledger, LedgerEntry};
+use crate::rpc::AccountSource;
+use crate::rpc_pool::RpcPool;
+use crate::wallets::WalletSigner;
+use solana_program::instruction::Instruction;
+use solana_sdk::address_lookup_table::instruction::{create_lookup_table, extend_lookup_table};
+use solana_sdk::address_lookup_table::state::{AddressLookupTable, LOOKUP_TABLE_MAX_ADDRESSES};
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Addresses added per extend transaction, keeping it well under the packet size limit.
+const EXTEND_BATCH_SIZE: usize = 20;
+const ACTIVATION_TIMEOUT: Duration = Duration::from_secs(30);
+const ACTIVATION_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// The `addresses` that are not in any of `tables`, without duplicates.
+pub fn missing_addresses(addresses: &[Pubkey], tables: &[AddressLookupTableAccount]) -> Vec<Pubkey> {
+    let covered: HashSet<&Pubkey> = tables.iter().flat_map(|t| t.addresses.iter()).collect();
+
+    let mut seen = HashSet::new();
+    addresses
+        .iter()
+        .filter(|pubkey| !covered.contains(pubkey) && seen.insert(**pubkey))
+        .copied()
+        .collect()
+}
+
+/// Maintains a lookup table owned by the wallet and adds the swap accounts
+/// that none of the configured tables cover.
+pub struct LookupTableManager {
+    rpc_pool: Arc<RpcPool>,
+    wallet_kp: Arc<WalletSigner>,
+    table: Option<Pubkey>,
+    ledger: Arc<Ledger>,
+}
+
+impl LookupTableManager {
+    pub fn new(
+        rpc_pool: Arc<RpcPool>,
+        wallet_kp: Arc<WalletSigner>,
+        config: &LookupTableConfig,
+        ledger: Arc<Ledger>,
+    ) -> anyhow::Result<Self> {
+        let table = config
+            .address
+            .as_ref()
+            .map(|address| Pubkey::from_str(address))
+            .transpose()?;
+        Ok(Self {
+            rpc_pool,
+            wallet_kp,
+            table,
+            ledger,
+        })
+    }
+
+    /// Makes sure every one of `addresses` is in one of `tables`, adding the
+    /// missing ones to the managed table and refreshing its entry in `tables`.
+    pub async fn cover(
+        &mut self,
+        addresses: &[Pubkey],
+        tables: &mut Vec<AddressLookupTableAccount>,
+    ) -> anyhow::Result<()> {
+        if let Some(table) = self.table {
+            if !tables.iter().any(|t| t.key == table) {
+                tables.push(self.fetch(&table)?);
+            }
+        }
+
+        let missing = missing_addresses(addresses, tables);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "{} swap accounts are not in any lookup table",
+            missing.len()
+        );
+
+        let table = match self.table {
+            Some(table) => table,
+            None => self.create()?,
+        };
+
+        let current = self.fetch(&table)?;
+        if current.addresses.len() + missing.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+            anyhow::bail!(
+                "Lookup table {} has room for {} more addresses but {} are missing",
+                table,
+                LOOKUP_TABLE_MAX_ADDRESSES - current.addresses.len(),
+                missing.len()
+            );
+        }
+
+        for batch in missing.chunks(EXTEND_BATCH_SIZE) {
+            let wallet = self.wallet_kp.pubkey();
+            let extend_ix = extend_lookup_table(table, wallet, Some(wallet), batch.to_vec());
+            let signature = self.send(extend_ix)?;
+            info!(
+                "Added {} addresses to lookup table {}: {}",
+                batch.len(),
+                table,
+                signature
+            );
+            self.ledger.record(LedgerEntry::LookupTableExtended {
+                address: table.to_string(),
+                added: batch.len(),
+                signature: signature.to_string(),
+            });
+        }
+
+        self.wait_for_activation(&table).await?;
+
+        let refreshed = self.fetch(&table)?;
+        tables.retain(|t| t.key != table);
+        tables.push(refreshed);
+        Ok(())
+    }
+
+    fn create(&mut self) -> anyhow::Result<Pubkey> {
+        let wallet = self.wallet_kp.pubkey();
+        let recent_slot = self
+            .rpc_pool
+            .primary()
+            .get_slot_with_commitment(CommitmentConfig::finalized())?;
+        let (create_ix, table) = create_lookup_table(wallet, wallet, recent_slot);
+        let signature = self.send(create_ix)?;
+        info!(
+            "Created lookup table {}: {}. Set lookup_table.address to reuse it after a restart",
+            table, signature
+        );
+        self.ledger.record(LedgerEntry::LookupTableCreated {
+            authority: wallet.to_string(),
+            address: table.to_string(),
+            signature: signature.to_string(),
+        });
+        self.table = Some(table);
+        Ok(table)
+    }
+
+    fn fetch(&self, table: &Pubkey) -> anyhow::Result<AddressLookupTableAccount> {
+        let account = self.rpc_pool.get_account(table)?;
+        let lookup_table = AddressLookupTable::deserialize(&account.data)?;
+        if lookup_table.meta.authority != Some(self.wallet_kp.pubkey()) {
+            anyhow::bail!("Lookup table {} is not owned by the wallet", table);
+        }
+        Ok(AddressLookupTableAccount {
+            key: *table,
+            addresses: lookup_table.addresses.into_owned(),
+        })
+    }
+
+    /// Addresses can only be looked up once the slot they were added in has passed.
+    async fn wait_for_activation(&self, table: &Pubkey) -> anyhow::Result<()> {
+        let account = self.rpc_pool.get_account(table)?;
+        let last_extended_slot = AddressLookupTable::deserialize(&account.data)?
+            .meta
+            .last_extended_slot;
+
+        let deadline = Instant::now() + ACTIVATION_TIMEOUT;
+        while self.rpc_pool.primary().get_slot()? <= last_extended_slot {
+            if Instant::now() > deadline {
+                anyhow::bail!("Lookup table {} did not activate in time", table);
+            }
+            tokio::time::sleep(ACTIVATION_POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    fn send(&self, instruction: Instruction) -> anyhow::Result<Signature> {
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
+            ComputeBudgetInstruction::set_compute_unit_limit(60_000),
+            instruction,
+        ];
+        let blockhash = self.rpc_pool.primary().get_latest_blockhash()?;
+        let mut tx = Transaction::new_with_payer(&instructions, Some(&self.wallet_kp.pubkey()));
+        tx.try_sign(&[self.wallet_kp.as_ref() as &dyn Signer], blockhash)?;
+        Ok(self.rpc_pool.primary().send_and_confirm_transaction(&tx)?)
+    }
+}
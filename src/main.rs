@@ -1,12 +1,35 @@
+mod backtest;
+mod base_assets;
+mod blockhash;
 mod bot;
 mod config;
 mod constants;
+mod cycles;
 mod dex;
+mod fixtures;
+mod keystore;
+mod ledger;
+mod lookup_table;
+mod nonce;
 mod pools;
+mod quote;
+mod recorder;
 mod refresh;
+mod reload;
+mod remote_signer;
+mod risk;
+mod rpc;
+mod rpc_pool;
+mod sender;
+mod snapshot;
+mod supervisor;
+mod tpu;
 mod transaction;
+mod wallets;
+mod wsol;
 
 use clap::{App, Arg};
+use solana_sdk::signer::Signer;
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
 
@@ -33,9 +56,195 @@ async fn main() -> anyhow::Result<()> {
                 .takes_value(true)
                 .default_value("config.toml"),
         )
+        .subcommand(
+            App::new("capture")
+                .about("Saves pool accounts as decoder test fixtures")
+                .arg(
+                    Arg::with_name("dex")
+                        .long("dex")
+                        .value_name("DEX")
+                        .help("Dex of the pools, named like the pool lists (raydium, pump, whirlpool, ...)")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .value_name("POOL")
+                        .help("Pool account to capture, can be repeated")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("rpc-url")
+                        .long("rpc-url")
+                        .value_name("URL")
+                        .help("RPC to read from, defaults to rpc.url of the config file")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .value_name("DIR")
+                        .help("Fixture directory")
+                        .takes_value(true)
+                        .default_value("tests/fixtures"),
+                ),
+        )
+        .subcommand(
+            App::new("backtest")
+                .about("Replays recorded account snapshots through the quote and profit checks")
+                .arg(
+                    Arg::with_name("snapshots")
+                        .long("snapshots")
+                        .value_name("PATH")
+                        .help("Snapshot file, or directory of snapshot files")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("mint")
+                        .long("mint")
+                        .value_name("MINT")
+                        .help("Only replay this mint of the config")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("compute-unit-price")
+                        .long("compute-unit-price")
+                        .value_name("MICRO_LAMPORTS")
+                        .help("Priority fee to charge, defaults to spam.compute_unit_price of the config")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            App::new("record")
+                .about("Polls the accounts of the configured mints and saves every change as a snapshot")
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .value_name("DIR")
+                        .help("Snapshot directory")
+                        .takes_value(true)
+                        .default_value("snapshots"),
+                )
+                .arg(
+                    Arg::with_name("mint")
+                        .long("mint")
+                        .value_name("MINT")
+                        .help("Only record this mint of the config, can be repeated")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("MS")
+                        .help("Poll interval in milliseconds")
+                        .takes_value(true)
+                        .default_value("400"),
+                )
+                .arg(
+                    Arg::with_name("max-file-mb")
+                        .long("max-file-mb")
+                        .value_name("MB")
+                        .help("Start a new file once the current one reaches this compressed size")
+                        .takes_value(true)
+                        .default_value("100"),
+                )
+                .arg(
+                    Arg::with_name("max-file-secs")
+                        .long("max-file-secs")
+                        .value_name("SECS")
+                        .help("Start a new file once the current one is this old")
+                        .takes_value(true)
+                        .default_value("3600"),
+                ),
+        )
+        .subcommand(
+            App::new("keystore")
+                .about("Encrypts a keypair into a passphrase-protected keystore file")
+                .arg(
+                    Arg::with_name("keypair")
+                        .long("keypair")
+                        .value_name("KEYPAIR")
+                        .help("Keypair file or base58 secret key to encrypt")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .value_name("FILE")
+                        .help("Keystore file to write")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
         .get_matches();
 
     let config_path = matches.value_of("config").unwrap();
+
+    if let Some(capture) = matches.subcommand_matches("capture") {
+        let rpc_url = match capture.value_of("rpc-url") {
+            Some(url) => url.to_string(),
+            None => config::Config::load(config_path)?.rpc.url,
+        };
+        let addresses: Vec<&str> = capture.values_of("address").unwrap().collect();
+        fixtures::capture_fixtures(
+            &rpc_url,
+            capture.value_of("dex").unwrap(),
+            &addresses,
+            std::path::Path::new(capture.value_of("out").unwrap()),
+        )?;
+        return Ok(());
+    }
+
+    if let Some(args) = matches.subcommand_matches("keystore") {
+        let keypair = wallets::load_keypair(args.value_of("keypair").unwrap())?;
+        let out = std::path::Path::new(args.value_of("out").unwrap());
+        keystore::create_keystore(&keypair, out)?;
+        info!("Keystore for {} written to {}", keypair.pubkey(), out.display());
+        return Ok(());
+    }
+
+    if let Some(backtest) = matches.subcommand_matches("backtest") {
+        let config = config::Config::load(config_path)?;
+        let compute_unit_price = match backtest.value_of("compute-unit-price") {
+            Some(price) => Some(price.parse()?),
+            None => None,
+        };
+        backtest::run_backtest_command(
+            &config,
+            std::path::Path::new(backtest.value_of("snapshots").unwrap()),
+            backtest.value_of("mint"),
+            compute_unit_price,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if let Some(record) = matches.subcommand_matches("record") {
+        let config = config::Config::load(config_path)?;
+        let options = recorder::RecordOptions {
+            out: std::path::PathBuf::from(record.value_of("out").unwrap()),
+            mints: record
+                .values_of("mint")
+                .map(|mints| mints.map(str::to_string).collect())
+                .unwrap_or_default(),
+            poll_interval: std::time::Duration::from_millis(
+                record.value_of("interval").unwrap().parse()?,
+            ),
+            max_file_bytes: Some(record.value_of("max-file-mb").unwrap().parse::<u64>()? << 20),
+            max_file_age: Some(std::time::Duration::from_secs(
+                record.value_of("max-file-secs").unwrap().parse()?,
+            )),
+        };
+        recorder::record(&config, options).await?;
+        return Ok(());
+    }
+
     info!("Using config file: {}", config_path);
 
     bot::run_bot(config_path).await?;
@@ -0,0 +1,216 @@
+use crate::ledger::{Ledger, LedgerEntry};
+use crate::rpc::AccountSource;
+use crate::rpc_pool::RpcPool;
+use crate::supervisor::Shutdown;
+use crate::wallets::WalletSigner;
+use solana_client::nonce_utils;
+use solana_program::instruction::Instruction;
+use solana_sdk::account::Account;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::nonce::state::State;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+const NONCE_SEED_PREFIX: &str = "arb-nonce-";
+
+/// Address of the `index`th nonce account of `wallet`. Derived with a seed, so the same
+/// accounts are found again after a restart without keeping their keypairs.
+pub fn nonce_address(wallet: &Pubkey, index: usize) -> anyhow::Result<Pubkey> {
+    Ok(Pubkey::create_with_seed(
+        wallet,
+        &format!("{}{}", NONCE_SEED_PREFIX, index),
+        &system_program::id(),
+    )?)
+}
+
+/// The current nonce value of a nonce account whose authority is `authority`.
+pub fn read_nonce(account: &Account, authority: &Pubkey) -> anyhow::Result<Hash> {
+    let data = nonce_utils::data_from_account(account)?;
+    if data.authority != *authority {
+        anyhow::bail!(
+            "Nonce authority is {}, expected {}",
+            data.authority,
+            authority
+        );
+    }
+    Ok(data.blockhash())
+}
+
+/// Creates the first `count` nonce accounts of the wallet that don't exist yet and returns all
+/// their addresses. The accounts are read through `AccountSource`; rent, the blockhash and the
+/// creating transactions go to the primary RPC.
+pub fn ensure_nonce_accounts(
+    rpc_pool: &RpcPool,
+    wallet_kp: &WalletSigner,
+    count: usize,
+    ledger: &Ledger,
+) -> anyhow::Result<Vec<Pubkey>> {
+    let wallet = wallet_kp.pubkey();
+    let addresses = (0..count)
+        .map(|i| nonce_address(&wallet, i))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let accounts = rpc_pool.get_multiple_accounts(&addresses)?;
+    let rpc_client = rpc_pool.primary();
+    let rent = rpc_client.get_minimum_balance_for_rent_exemption(State::size())?;
+
+    for (i, (address, account)) in addresses.iter().zip(accounts).enumerate() {
+        if account.is_some() {
+            continue;
+        }
+        let mut instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
+            ComputeBudgetInstruction::set_compute_unit_limit(10_000),
+        ];
+        instructions.extend(system_instruction::create_nonce_account_with_seed(
+            &wallet,
+            address,
+            &wallet,
+            &format!("{}{}", NONCE_SEED_PREFIX, i),
+            &wallet,
+            rent,
+        ));
+        let blockhash = rpc_client.get_latest_blockhash()?;
+        let mut tx = Transaction::new_with_payer(&instructions, Some(&wallet));
+        tx.try_sign(&[wallet_kp as &dyn Signer], blockhash)?;
+        let signature = rpc_client.send_and_confirm_transaction(&tx)?;
+        info!("Created nonce account {}: {}", address, signature);
+        ledger.record(LedgerEntry::NonceAccountCreated {
+            authority: wallet.to_string(),
+            address: address.to_string(),
+            signature: signature.to_string(),
+        });
+    }
+    Ok(addresses)
+}
+
+struct NonceEntry {
+    address: Pubkey,
+    /// Last value read from the chain. `None` until the account has been read.
+    nonce: Option<Hash>,
+    /// The value handed to a transaction and when, until the chain shows it advanced.
+    used: Option<(Hash, Instant)>,
+}
+
+/// Hands out nonce accounts so that transactions sent at the same time each advance a
+/// different one. A nonce is handed out again once the chain shows it advanced, or after
+/// `reuse_after` when its transaction apparently didn't land.
+pub struct NoncePool {
+    authority: Pubkey,
+    reuse_after: Duration,
+    state: Mutex<NonceState>,
+}
+
+struct NonceState {
+    entries: Vec<NonceEntry>,
+    next: usize,
+}
+
+impl NoncePool {
+    pub fn new(authority: Pubkey, addresses: Vec<Pubkey>, reuse_after: Duration) -> Self {
+        let entries = addresses
+            .into_iter()
+            .map(|address| NonceEntry {
+                address,
+                nonce: None,
+                used: None,
+            })
+            .collect();
+        Self {
+            authority,
+            reuse_after,
+            state: Mutex::new(NonceState { entries, next: 0 }),
+        }
+    }
+
+    pub fn addresses(&self) -> Vec<Pubkey> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.iter().map(|e| e.address).collect()
+    }
+
+    /// Records the value read from the chain for `address`.
+    pub fn update(&self, address: &Pubkey, nonce: Hash) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = state.entries.iter_mut().find(|e| e.address == *address) {
+            if entry.used.is_some_and(|(used, _)| used != nonce) {
+                entry.used = None;
+            }
+            entry.nonce = Some(nonce);
+        }
+    }
+
+    /// The next free nonce account and its value, marked as used. `None` when every nonce is
+    /// still held by a recent transaction.
+    pub fn acquire(&self) -> Option<(Pubkey, Hash)> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let count = state.entries.len();
+        for offset in 0..count {
+            let index = (state.next + offset) % count;
+            let entry = &mut state.entries[index];
+            let Some(nonce) = entry.nonce else {
+                continue;
+            };
+            if entry
+                .used
+                .is_some_and(|(_, at)| at.elapsed() < self.reuse_after)
+            {
+                continue;
+            }
+            entry.used = Some((nonce, Instant::now()));
+            let address = entry.address;
+            state.next = (index + 1) % count;
+            return Some((address, nonce));
+        }
+        None
+    }
+
+    /// Frees a nonce that was acquired but never sent.
+    pub fn release(&self, address: &Pubkey) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = state.entries.iter_mut().find(|e| e.address == *address) {
+            entry.used = None;
+        }
+    }
+
+    /// The instruction that has to come first in a transaction using the nonce of `address`.
+    pub fn advance_instruction(&self, address: &Pubkey) -> Instruction {
+        system_instruction::advance_nonce_account(address, &self.authority)
+    }
+
+    /// Reads every nonce account once.
+    pub fn refresh(&self, accounts: &dyn AccountSource) -> anyhow::Result<()> {
+        let addresses = self.addresses();
+        let accounts = accounts.get_multiple_accounts(&addresses)?;
+        for (address, account) in addresses.iter().zip(accounts) {
+            let Some(account) = account else {
+                error!("Nonce account {} does not exist", address);
+                continue;
+            };
+            match read_nonce(&account, &self.authority) {
+                Ok(nonce) => self.update(address, nonce),
+                Err(e) => error!("Failed to read nonce account {}: {:?}", address, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn nonce_refresher(
+    rpc_pool: Arc<RpcPool>,
+    nonce_pool: Arc<NoncePool>,
+    refresh_interval: Duration,
+    mut shutdown: Shutdown,
+) {
+    while !shutdown.is_triggered() {
+        if let Err(e) = nonce_pool.refresh(rpc_pool.as_ref()) {
+            error!("Failed to refresh nonce accounts: {:?}", e);
+        }
+        shutdown.sleep(refresh_interval).await;
+    }
+}
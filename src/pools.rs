@@ -1,9 +1,18 @@
 use crate::{
-    constants::SOL_MINT,
-    dex::raydium::{clmm_info::POOL_TICK_ARRAY_BITMAP_SEED, raydium_clmm_program_id},
+    dex::heaven::heaven_program_id,
+    dex::meteora::constants::{damm_program_id, damm_v2_program_id, dlmm_program_id},
+    dex::pump::pump_program_id,
+    dex::raydium::{
+        clmm_info::POOL_TICK_ARRAY_BITMAP_SEED, raydium_clmm_program_id, raydium_cp_program_id,
+        raydium_program_id,
+    },
+    dex::solfi::constants::solfi_program_id,
+    dex::vertigo::vertigo_program_id,
+    dex::whirlpool::constants::whirlpool_program_id,
 };
 use solana_program::instruction::AccountMeta;
 use solana_program::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 #[derive(Debug, Clone)]
@@ -134,12 +143,91 @@ pub struct HeavenPool {
     pub token_program: Pubkey, // Support for Token-2022
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DexKind {
+    Raydium,
+    RaydiumCp,
+    Pump,
+    Dlmm,
+    Whirlpool,
+    RaydiumClmm,
+    MeteoraDAmm,
+    Solfi,
+    MeteoraDAmmV2,
+    Vertigo,
+    Heaven,
+}
+
+impl DexKind {
+    pub const ALL: [DexKind; 11] = [
+        DexKind::Raydium,
+        DexKind::RaydiumCp,
+        DexKind::Pump,
+        DexKind::Dlmm,
+        DexKind::Whirlpool,
+        DexKind::RaydiumClmm,
+        DexKind::MeteoraDAmm,
+        DexKind::Solfi,
+        DexKind::MeteoraDAmmV2,
+        DexKind::Vertigo,
+        DexKind::Heaven,
+    ];
+
+    /// Name used on the command line and for fixture directories, matching the pool list names in the config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DexKind::Raydium => "raydium",
+            DexKind::RaydiumCp => "raydium_cp",
+            DexKind::Pump => "pump",
+            DexKind::Dlmm => "meteora_dlmm",
+            DexKind::Whirlpool => "whirlpool",
+            DexKind::RaydiumClmm => "raydium_clmm",
+            DexKind::MeteoraDAmm => "meteora_damm",
+            DexKind::Solfi => "solfi",
+            DexKind::MeteoraDAmmV2 => "meteora_damm_v2",
+            DexKind::Vertigo => "vertigo",
+            DexKind::Heaven => "heaven",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DexKind> {
+        Self::ALL.into_iter().find(|dex| dex.name() == name)
+    }
+
+    /// Program that owns the pool accounts of this dex.
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            DexKind::Raydium => raydium_program_id(),
+            DexKind::RaydiumCp => raydium_cp_program_id(),
+            DexKind::Pump => pump_program_id(),
+            DexKind::Dlmm => dlmm_program_id(),
+            DexKind::Whirlpool => whirlpool_program_id(),
+            DexKind::RaydiumClmm => raydium_clmm_program_id(),
+            DexKind::MeteoraDAmm => damm_program_id(),
+            DexKind::Solfi => solfi_program_id(),
+            DexKind::MeteoraDAmmV2 => damm_v2_program_id(),
+            DexKind::Vertigo => vertigo_program_id(),
+            DexKind::Heaven => heaven_program_id(),
+        }
+    }
+}
+
+/// One pool of any dex, reduced to the two mints it trades and their vaults.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolRef {
+    pub dex: DexKind,
+    pub pool: Pubkey,
+    pub token_mint: Pubkey,
+    pub base_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub base_vault: Pubkey,
+}
+
 #[derive(Debug, Clone)]
 pub struct MintPoolData {
     pub mint: Pubkey,
     pub token_program: Pubkey, // Support for both Token and Token 2022
     pub wallet_account: Pubkey,
-    pub wallet_wsol_account: Pubkey,
     pub raydium_pools: Vec<RaydiumPool>,
     pub raydium_cp_pools: Vec<RaydiumCpPool>,
     pub pump_pools: Vec<PumpPool>,
@@ -151,19 +239,17 @@ pub struct MintPoolData {
     pub meteora_damm_v2_pools: Vec<MeteoraDAmmV2Pool>,
     pub vertigo_pools: Vec<VertigoPool>,
     pub heaven_pools: Vec<HeavenPool>,
+    /// Base token balance of each pool's vault, keyed by pool address.
+    pub liquidity: HashMap<Pubkey, u64>,
 }
 
 impl MintPoolData {
     pub fn new(mint: &str, wallet_account: &str, token_program: Pubkey) -> anyhow::Result<Self> {
-        let sol_mint = Pubkey::from_str(SOL_MINT)?;
         let wallet_pk = Pubkey::from_str(wallet_account)?;
-        let wallet_wsol_pk =
-            spl_associated_token_account::get_associated_token_address(&wallet_pk, &sol_mint);
         Ok(Self {
             mint: Pubkey::from_str(mint)?,
             token_program,
             wallet_account: wallet_pk,
-            wallet_wsol_account: wallet_wsol_pk,
             raydium_pools: Vec::new(),
             raydium_cp_pools: Vec::new(),
             pump_pools: Vec::new(),
@@ -175,9 +261,191 @@ impl MintPoolData {
             meteora_damm_v2_pools: Vec::new(),
             vertigo_pools: Vec::new(),
             heaven_pools: Vec::new(),
+            liquidity: HashMap::new(),
         })
     }
 
+    /// Dex-independent view of every pool.
+    pub fn pools(&self) -> Vec<PoolRef> {
+        let pool_ref = |dex, pool, token_mint, base_mint, token_vault, base_vault| PoolRef {
+            dex,
+            pool,
+            token_mint,
+            base_mint,
+            token_vault,
+            base_vault,
+        };
+        // Concentrated liquidity pools order their vaults by mint address
+        let sorted_vaults = |base_mint: &Pubkey, token_mint: &Pubkey, x: Pubkey, y: Pubkey| {
+            if base_mint < token_mint {
+                (y, x)
+            } else {
+                (x, y)
+            }
+        };
+
+        let mut pools = Vec::new();
+        pools.extend(self.raydium_pools.iter().map(|p| {
+            pool_ref(
+                DexKind::Raydium,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                p.token_vault,
+                p.sol_vault,
+            )
+        }));
+        pools.extend(self.raydium_cp_pools.iter().map(|p| {
+            pool_ref(
+                DexKind::RaydiumCp,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                p.token_vault,
+                p.sol_vault,
+            )
+        }));
+        pools.extend(self.pump_pools.iter().map(|p| {
+            pool_ref(
+                DexKind::Pump,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                p.token_vault,
+                p.sol_vault,
+            )
+        }));
+        pools.extend(self.dlmm_pairs.iter().map(|p| {
+            pool_ref(
+                DexKind::Dlmm,
+                p.pair,
+                p.token_mint,
+                p.base_mint,
+                p.token_vault,
+                p.sol_vault,
+            )
+        }));
+        pools.extend(self.whirlpool_pools.iter().map(|p| {
+            let (token_vault, base_vault) =
+                sorted_vaults(&p.base_mint, &p.token_mint, p.x_vault, p.y_vault);
+            pool_ref(
+                DexKind::Whirlpool,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                token_vault,
+                base_vault,
+            )
+        }));
+        pools.extend(self.raydium_clmm_pools.iter().map(|p| {
+            let (token_vault, base_vault) =
+                sorted_vaults(&p.base_mint, &p.token_mint, p.x_vault, p.y_vault);
+            pool_ref(
+                DexKind::RaydiumClmm,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                token_vault,
+                base_vault,
+            )
+        }));
+        pools.extend(self.meteora_damm_pools.iter().map(|p| {
+            pool_ref(
+                DexKind::MeteoraDAmm,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                p.token_x_token_vault,
+                p.token_sol_token_vault,
+            )
+        }));
+        pools.extend(self.solfi_pools.iter().map(|p| {
+            pool_ref(
+                DexKind::Solfi,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                p.token_x_vault,
+                p.token_sol_vault,
+            )
+        }));
+        pools.extend(self.meteora_damm_v2_pools.iter().map(|p| {
+            pool_ref(
+                DexKind::MeteoraDAmmV2,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                p.token_x_vault,
+                p.token_sol_vault,
+            )
+        }));
+        pools.extend(self.vertigo_pools.iter().map(|p| {
+            pool_ref(
+                DexKind::Vertigo,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                p.token_x_vault,
+                p.token_sol_vault,
+            )
+        }));
+        pools.extend(self.heaven_pools.iter().map(|p| {
+            pool_ref(
+                DexKind::Heaven,
+                p.pool,
+                p.token_mint,
+                p.base_mint,
+                p.token_x_vault,
+                p.token_base_vault,
+            )
+        }));
+        pools
+    }
+
+    /// Pool addresses, deepest liquidity first. Balances of different bases are in different
+    /// units, so pools are only ranked against pools on the same base and the bases take turns:
+    /// the deepest pool of every base, then the second deepest, and so on. Pools without a known
+    /// balance come last.
+    pub fn pools_by_liquidity(&self) -> Vec<Pubkey> {
+        let mut pools: Vec<(Pubkey, Pubkey, Option<u64>)> = self
+            .pools()
+            .into_iter()
+            .map(|p| (p.base_mint, p.pool, self.liquidity.get(&p.pool).copied()))
+            .collect();
+        pools.sort_by_key(|(base_mint, _, amount)| (*base_mint, std::cmp::Reverse(*amount)));
+
+        let mut ranked = Vec::with_capacity(pools.len());
+        let mut rank = 0;
+        for (i, (base_mint, pool, amount)) in pools.iter().enumerate() {
+            rank = if i > 0 && pools[i - 1].0 == *base_mint {
+                rank + 1
+            } else {
+                0
+            };
+            ranked.push((amount.is_none(), rank, *base_mint, *pool));
+        }
+        ranked.sort();
+        ranked.into_iter().map(|(_, _, _, pool)| pool).collect()
+    }
+
+    /// Copy of this data that only keeps the pools in `keep`.
+    pub fn with_pools(&self, keep: &HashSet<Pubkey>) -> MintPoolData {
+        let mut data = self.clone();
+        data.raydium_pools.retain(|p| keep.contains(&p.pool));
+        data.raydium_cp_pools.retain(|p| keep.contains(&p.pool));
+        data.pump_pools.retain(|p| keep.contains(&p.pool));
+        data.dlmm_pairs.retain(|p| keep.contains(&p.pair));
+        data.whirlpool_pools.retain(|p| keep.contains(&p.pool));
+        data.raydium_clmm_pools.retain(|p| keep.contains(&p.pool));
+        data.meteora_damm_pools.retain(|p| keep.contains(&p.pool));
+        data.solfi_pools.retain(|p| keep.contains(&p.pool));
+        data.meteora_damm_v2_pools
+            .retain(|p| keep.contains(&p.pool));
+        data.vertigo_pools.retain(|p| keep.contains(&p.pool));
+        data.heaven_pools.retain(|p| keep.contains(&p.pool));
+        data
+    }
+
     pub fn add_raydium_pool(
         &mut self,
         pool: &str,
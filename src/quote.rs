@@ -0,0 +1,31 @@
+use crate::pools::DexKind;
+
+/// Swap fee of the dexes that can be quoted from vault balances alone, in basis points.
+/// The fees are fixed per dex rather than read from the pool. DLMM, Whirlpool, Raydium CLMM,
+/// Meteora DAMM and DAMM v2, SolFi, Vertigo and Heaven need their tick, bin or curve state and
+/// return `None`; the cycle scanner logs the pools it leaves out for that.
+pub fn constant_product_fee_bps(dex: DexKind) -> Option<u64> {
+    match dex {
+        DexKind::Raydium => Some(25),
+        // Most CP pools use the 0.25% config; pools on a higher fee tier are quoted optimistically
+        DexKind::RaydiumCp => Some(25),
+        // 0.20% LP fee, 0.05% protocol fee and 0.05% coin creator fee
+        DexKind::Pump => Some(30),
+        _ => None,
+    }
+}
+
+/// Output of a constant product swap with the fee taken from the input.
+pub fn constant_product_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_bps: u64,
+) -> u64 {
+    if reserve_in == 0 || reserve_out == 0 {
+        return 0;
+    }
+    let amount_in = amount_in as u128 * (10_000 - fee_bps.min(10_000)) as u128 / 10_000;
+    let out = amount_in * reserve_out as u128 / (reserve_in as u128 + amount_in);
+    out as u64
+}
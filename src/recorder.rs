@@ -0,0 +1,190 @@
+use crate::base_assets::BaseAssets;
+use crate::config::{Config, MintConfig};
+use crate::pools::MintPoolData;
+use crate::refresh::initialize_pool_data;
+use crate::snapshot::{AccountSnapshot, SnapshotWriter};
+use crate::transaction::{create_swap_instruction, executable_pools, split_by_base};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+/// How often the pools are reloaded to pick up the tick and bin arrays the swaps use now.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct RecordOptions {
+    pub out: PathBuf,
+    /// Only record these mints of the config. Every mint when empty.
+    pub mints: Vec<String>,
+    pub poll_interval: Duration,
+    pub max_file_bytes: Option<u64>,
+    pub max_file_age: Option<Duration>,
+}
+
+/// The accounts a swap of `pool_data` reads: the mint, every pool and vault, and whatever else the
+/// swap instructions reference, such as tick and bin arrays, DAMM vaults and bridge pools.
+pub fn tracked_accounts(
+    pool_data: &MintPoolData,
+    base_assets: &BaseAssets,
+    compute_unit_limit: u32,
+) -> anyhow::Result<BTreeSet<Pubkey>> {
+    let mut accounts = BTreeSet::from([pool_data.mint]);
+    for pool in pool_data.pools() {
+        accounts.extend([pool.pool, pool.token_vault, pool.base_vault]);
+    }
+
+    // Accounts of the throwaway wallet don't exist and are never recorded
+    let wallet = Keypair::new();
+    let executable = executable_pools(pool_data, base_assets);
+    for group in split_by_base(&executable, false) {
+        let swap_ix =
+            create_swap_instruction(&wallet, &group, base_assets, compute_unit_limit, false, 0)?;
+        accounts.extend(
+            swap_ix
+                .accounts
+                .iter()
+                .filter(|meta| !meta.is_signer)
+                .map(|meta| meta.pubkey),
+        );
+    }
+    Ok(accounts)
+}
+
+/// The accounts to record for `mint_configs`. Tick and bin arrays follow the price, so this is
+/// recomputed while recording.
+async fn load_tracked_accounts(
+    config: &Config,
+    mint_configs: &[&MintConfig],
+    rpc_client: &Arc<RpcClient>,
+    base_assets: &BaseAssets,
+) -> anyhow::Result<BTreeSet<Pubkey>> {
+    let mut tracked = BTreeSet::new();
+    for mint_config in mint_configs {
+        let pool_data = initialize_pool_data(
+            &mint_config.mint,
+            &Pubkey::default().to_string(),
+            mint_config.raydium_pool_list.as_ref(),
+            mint_config.raydium_cp_pool_list.as_ref(),
+            mint_config.pump_pool_list.as_ref(),
+            mint_config.meteora_dlmm_pool_list.as_ref(),
+            mint_config.whirlpool_pool_list.as_ref(),
+            mint_config.raydium_clmm_pool_list.as_ref(),
+            mint_config.meteora_damm_pool_list.as_ref(),
+            mint_config.solfi_pool_list.as_ref(),
+            mint_config.meteora_damm_v2_pool_list.as_ref(),
+            mint_config.vertigo_pool_list.as_ref(),
+            mint_config.heaven_pool_list.as_ref(),
+            rpc_client.clone(),
+        )
+        .await?;
+        let accounts = tracked_accounts(&pool_data, base_assets, config.bot.compute_unit_limit)?;
+        debug!(
+            "{} accounts to record for mint {}",
+            accounts.len(),
+            mint_config.mint
+        );
+        tracked.extend(accounts);
+    }
+    Ok(tracked)
+}
+
+/// Backs the `record` command: polls the accounts of the configured mints and writes every change
+/// as a snapshot until interrupted.
+pub async fn record(config: &Config, options: RecordOptions) -> anyhow::Result<()> {
+    let rpc_client = Arc::new(RpcClient::new(config.rpc.url.clone()));
+    let base_assets = BaseAssets::from_config(config)?;
+
+    let mint_configs: Vec<&MintConfig> = config
+        .routing
+        .mint_config_list
+        .iter()
+        .filter(|m| options.mints.is_empty() || options.mints.contains(&m.mint))
+        .collect();
+    if mint_configs.is_empty() {
+        anyhow::bail!("No mint of the config matches {}", options.mints.join(", "));
+    }
+
+    let mut tracked =
+        load_tracked_accounts(config, &mint_configs, &rpc_client, &base_assets).await?;
+    info!(
+        "Recording {} accounts of {} mint(s)",
+        tracked.len(),
+        mint_configs.len()
+    );
+    // Programs referenced by the swaps only change on upgrades
+    let mut programs: HashSet<Pubkey> = HashSet::new();
+
+    let mut writer =
+        SnapshotWriter::new(&options.out, options.max_file_bytes, options.max_file_age)?;
+    let mut last_data: HashMap<Pubkey, Vec<u8>> = HashMap::new();
+    let mut interval = tokio::time::interval(options.poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut rescan = tokio::time::interval_at(
+        tokio::time::Instant::now() + RESCAN_INTERVAL,
+        RESCAN_INTERVAL,
+    );
+    rescan.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = rescan.tick() => {
+                let accounts =
+                    load_tracked_accounts(config, &mint_configs, &rpc_client, &base_assets).await;
+                match accounts {
+                    Ok(accounts) => {
+                        let before = tracked.len();
+                        tracked.extend(accounts.into_iter().filter(|a| !programs.contains(a)));
+                        if tracked.len() > before {
+                            info!(
+                                "Recording {} more accounts the swaps now use",
+                                tracked.len() - before
+                            );
+                        }
+                    }
+                    Err(e) => error!("Error reloading recorded accounts: {:?}", e),
+                }
+                continue;
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        let addresses: Vec<Pubkey> = tracked.iter().copied().collect();
+        for chunk in addresses.chunks(100) {
+            let response = match rpc_client
+                .get_multiple_accounts_with_commitment(chunk, rpc_client.commitment())
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    error!("Error polling recorded accounts: {:?}", e);
+                    continue;
+                }
+            };
+            let slot = response.context.slot;
+            for (address, account) in chunk.iter().zip(response.value) {
+                let Some(account) = account else {
+                    continue;
+                };
+                if account.executable {
+                    programs.insert(*address);
+                    continue;
+                }
+                if last_data.get(address) == Some(&account.data) {
+                    continue;
+                }
+                writer.write(&AccountSnapshot::new(slot, address, &account))?;
+                last_data.insert(*address, account.data);
+            }
+        }
+        tracked.retain(|address| !programs.contains(address));
+        writer.flush()?;
+    }
+
+    writer.finish()?;
+    info!("Recording stopped");
+    Ok(())
+}
@@ -1,5 +1,6 @@
-use crate::constants::sol_mint;
+use crate::dex::DecodeError;
 use crate::dex::meteora::constants::{damm_program_id, damm_v2_program_id};
+use crate::dex::meteora::damm_info::load_damm_pool;
 use crate::dex::meteora::dammv2_info::MeteoraDAmmV2Info;
 use crate::dex::meteora::{constants::dlmm_program_id, dlmm_info::DlmmInfo};
 use crate::dex::pump::{pump_fee_wallet, pump_mayhem_fee_wallet, pump_program_id, PumpAmmInfo};
@@ -15,13 +16,24 @@ use crate::dex::whirlpool::{
     constants::whirlpool_program_id, state::Whirlpool, update_tick_array_accounts_for_onchain,
 };
 use crate::pools::*;
-use solana_client::rpc_client::RpcClient;
+use crate::rpc::AccountSource;
 use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
 use spl_associated_token_account;
 use std::str::FromStr;
 use std::sync::Arc;
 use tracing::{error, info};
 
+/// Orders a pool's two sides as (token, base) for `mint`, where `a` is the side whose mint
+/// is `mint_a`. The side that isn't the mint is the base, which is not always SOL.
+fn token_and_base<T>(mint: &Pubkey, mint_a: &Pubkey, a: T, b: T) -> (T, T) {
+    if mint == mint_a {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
 pub async fn initialize_pool_data(
     mint: &str,
     wallet_account: &str,
@@ -36,7 +48,7 @@ pub async fn initialize_pool_data(
     meteora_damm_v2_pools: Option<&Vec<String>>,
     vertigo_pools: Option<&Vec<String>>,
     heaven_pools: Option<&Vec<String>>,
-    rpc_client: Arc<RpcClient>,
+    rpc_client: Arc<dyn AccountSource>,
 ) -> anyhow::Result<MintPoolData> {
     info!("Initializing pool data for mint: {}", mint);
 
@@ -77,22 +89,12 @@ pub async fn initialize_pool_data(
 
                     match PumpAmmInfo::load_checked(&account.data) {
                         Ok(amm_info) => {
-                            let (sol_vault, token_vault) = if sol_mint() == amm_info.base_mint {
-                                (
-                                    amm_info.pool_base_token_account,
-                                    amm_info.pool_quote_token_account,
-                                )
-                            } else if sol_mint() == amm_info.quote_mint {
-                                (
-                                    amm_info.pool_quote_token_account,
-                                    amm_info.pool_base_token_account,
-                                )
-                            } else {
-                                (
-                                    amm_info.pool_quote_token_account,
-                                    amm_info.pool_base_token_account,
-                                )
-                            };
+                            let (token_vault, sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &amm_info.base_mint,
+                                amm_info.pool_base_token_account,
+                                amm_info.pool_quote_token_account,
+                            );
 
                             let (fee_wallet, fee_token_wallet) =
                                 if amm_info.is_mayhem_mode {
@@ -122,11 +124,12 @@ pub async fn initialize_pool_data(
                                 );
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == amm_info.base_mint {
-                                (amm_info.base_mint, amm_info.quote_mint)
-                            } else {
-                                (amm_info.quote_mint, amm_info.base_mint)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &amm_info.base_mint,
+                                amm_info.base_mint,
+                                amm_info.quote_mint,
+                            );
                             
                             pool_data.add_pump_pool(
                                 pool_address,
@@ -144,7 +147,7 @@ pub async fn initialize_pool_data(
                             info!("    Base mint: {}", amm_info.base_mint.to_string());
                             info!("    Quote mint: {}", amm_info.quote_mint.to_string());
                             info!("    Token vault: {}", token_vault.to_string());
-                            info!("    Sol vault: {}", sol_vault.to_string());
+                            info!("    Base vault: {}", sol_vault.to_string());
                             info!("    Fee wallet: {}", fee_wallet.to_string());
                             info!("    Fee token wallet: {}", fee_token_wallet.to_string());
                             info!(
@@ -163,7 +166,7 @@ pub async fn initialize_pool_data(
                                 "Error parsing AmmInfo from Pump pool {}: {:?}",
                                 pump_pool_pubkey, e
                             );
-                            return Err(e);
+                            return Err(e.into());
                         }
                     }
                 }
@@ -203,35 +206,27 @@ pub async fn initialize_pool_data(
                                     "Mint {} is not present in Raydium pool {}, skipping",
                                     pool_data.mint, raydium_pool_pubkey
                                 );
-                                return Err(anyhow::anyhow!(
-                                    "Invalid Raydium pool: {}",
-                                    raydium_pool_pubkey
-                                ));
-                            }
-
-                            if amm_info.coin_mint != sol_mint() && amm_info.pc_mint != sol_mint() {
-                                error!(
-                                    "SOL is not present in Raydium pool {}",
-                                    raydium_pool_pubkey
-                                );
-                                return Err(anyhow::anyhow!(
-                                    "SOL is not present in Raydium pool: {}",
-                                    raydium_pool_pubkey
-                                ));
+                                return Err(DecodeError::MintMismatch {
+                                    mint: pool_data.mint,
+                                    pool: raydium_pool_pubkey,
+                                }
+                                .into());
                             }
 
-                            let (sol_vault, token_vault) = if sol_mint() == amm_info.coin_mint {
-                                (amm_info.coin_vault, amm_info.pc_vault)
-                            } else {
-                                (amm_info.pc_vault, amm_info.coin_vault)
-                            };
+                            let (token_vault, sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &amm_info.coin_mint,
+                                amm_info.coin_vault,
+                                amm_info.pc_vault,
+                            );
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == amm_info.coin_mint {
-                                (amm_info.coin_mint, amm_info.pc_mint)
-                            } else {
-                                (amm_info.pc_mint, amm_info.coin_mint)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &amm_info.coin_mint,
+                                amm_info.coin_mint,
+                                amm_info.pc_mint,
+                            );
                             
                             pool_data.add_raydium_pool(
                                 pool_address,
@@ -244,7 +239,7 @@ pub async fn initialize_pool_data(
                             info!("    Coin mint: {}", amm_info.coin_mint.to_string());
                             info!("    PC mint: {}", amm_info.pc_mint.to_string());
                             info!("    Token vault: {}", token_vault.to_string());
-                            info!("    Sol vault: {}", sol_vault.to_string());
+                            info!("    Base vault: {}", sol_vault.to_string());
                             info!("    Initialized Raydium pool: {}\n", raydium_pool_pubkey);
                         }
                         Err(e) => {
@@ -252,7 +247,7 @@ pub async fn initialize_pool_data(
                                 "Error parsing AmmInfo from Raydium pool {}: {:?}",
                                 raydium_pool_pubkey, e
                             );
-                            return Err(e);
+                            return Err(e.into());
                         }
                     }
                 }
@@ -292,33 +287,27 @@ pub async fn initialize_pool_data(
                                     "Mint {} is not present in Raydium CP pool {}, skipping",
                                     pool_data.mint, raydium_cp_pool_pubkey
                                 );
-                                return Err(anyhow::anyhow!(
-                                    "Invalid Raydium CP pool: {}",
-                                    raydium_cp_pool_pubkey
-                                ));
+                                return Err(DecodeError::MintMismatch {
+                                    mint: pool_data.mint,
+                                    pool: raydium_cp_pool_pubkey,
+                                }
+                                .into());
                             }
 
-                            let (sol_vault, token_vault) = if sol_mint() == amm_info.token_0_mint {
-                                (amm_info.token_0_vault, amm_info.token_1_vault)
-                            } else if sol_mint() == amm_info.token_1_mint {
-                                (amm_info.token_1_vault, amm_info.token_0_vault)
-                            } else {
-                                error!(
-                                    "SOL is not present in Raydium CP pool {}",
-                                    raydium_cp_pool_pubkey
-                                );
-                                return Err(anyhow::anyhow!(
-                                    "SOL is not present in Raydium CP pool: {}",
-                                    raydium_cp_pool_pubkey
-                                ));
-                            };
+                            let (token_vault, sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &amm_info.token_0_mint,
+                                amm_info.token_0_vault,
+                                amm_info.token_1_vault,
+                            );
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == amm_info.token_0_mint {
-                                (amm_info.token_0_mint, amm_info.token_1_mint)
-                            } else {
-                                (amm_info.token_1_mint, amm_info.token_0_mint)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &amm_info.token_0_mint,
+                                amm_info.token_0_mint,
+                                amm_info.token_1_mint,
+                            );
                             
                             pool_data.add_raydium_cp_pool(
                                 pool_address,
@@ -331,7 +320,7 @@ pub async fn initialize_pool_data(
                             )?;
                             info!("Raydium CP pool added: {}", pool_address);
                             info!("    Token vault: {}", token_vault.to_string());
-                            info!("    Sol vault: {}", sol_vault.to_string());
+                            info!("    Base vault: {}", sol_vault.to_string());
                             info!("    AMM Config: {}", amm_info.amm_config.to_string());
                             info!(
                                 "    Observation Key: {}\n",
@@ -343,7 +332,7 @@ pub async fn initialize_pool_data(
                                 "Error parsing AmmInfo from Raydium CP pool {}: {:?}",
                                 raydium_cp_pool_pubkey, e
                             );
-                            return Err(e);
+                            return Err(e.into());
                         }
                     }
                 }
@@ -375,9 +364,12 @@ pub async fn initialize_pool_data(
 
                     match DlmmInfo::load_checked(&account.data) {
                         Ok(amm_info) => {
-                            let sol_mint = sol_mint();
-                            let (token_vault, sol_vault) =
-                                amm_info.get_token_and_sol_vaults(&pool_data.mint, &sol_mint);
+                            let (token_vault, sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &amm_info.token_x_mint,
+                                amm_info.token_x_vault,
+                                amm_info.token_y_vault,
+                            );
 
                             let bin_arrays = match amm_info.calculate_bin_arrays(&dlmm_pool_pubkey)
                             {
@@ -397,11 +389,12 @@ pub async fn initialize_pool_data(
                                 bin_array_strings.iter().map(|s| s.as_str()).collect();
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == amm_info.token_x_mint {
-                                (amm_info.token_x_mint, amm_info.token_y_mint)
-                            } else {
-                                (amm_info.token_y_mint, amm_info.token_x_mint)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &amm_info.token_x_mint,
+                                amm_info.token_x_mint,
+                                amm_info.token_y_mint,
+                            );
                             
                             pool_data.add_dlmm_pool(
                                 pool_address,
@@ -418,7 +411,7 @@ pub async fn initialize_pool_data(
                             info!("    Token X Mint: {}", amm_info.token_x_mint.to_string());
                             info!("    Token Y Mint: {}", amm_info.token_y_mint.to_string());
                             info!("    Token vault: {}", token_vault.to_string());
-                            info!("    Sol vault: {}", sol_vault.to_string());
+                            info!("    Base vault: {}", sol_vault.to_string());
                             info!("    Oracle: {}", amm_info.oracle.to_string());
                             info!("    Active ID: {}", amm_info.active_id);
 
@@ -432,7 +425,7 @@ pub async fn initialize_pool_data(
                                 "Error parsing AmmInfo from DLMM pool {}: {:?}",
                                 dlmm_pool_pubkey, e
                             );
-                            return Err(e);
+                            return Err(e.into());
                         }
                     }
                 }
@@ -472,27 +465,19 @@ pub async fn initialize_pool_data(
                                     "Mint {} is not present in Whirlpool pool {}, skipping",
                                     pool_data.mint, whirlpool_pool_pubkey
                                 );
-                                return Err(anyhow::anyhow!(
-                                    "Invalid Whirlpool pool: {}",
-                                    whirlpool_pool_pubkey
-                                ));
+                                return Err(DecodeError::MintMismatch {
+                                    mint: pool_data.mint,
+                                    pool: whirlpool_pool_pubkey,
+                                }
+                                .into());
                             }
 
-                            let sol_mint = sol_mint();
-                            let (sol_vault, token_vault) = if sol_mint == whirlpool.token_mint_a {
-                                (whirlpool.token_vault_a, whirlpool.token_vault_b)
-                            } else if sol_mint == whirlpool.token_mint_b {
-                                (whirlpool.token_vault_b, whirlpool.token_vault_a)
-                            } else {
-                                error!(
-                                    "SOL is not present in Whirlpool pool {}",
-                                    whirlpool_pool_pubkey
-                                );
-                                return Err(anyhow::anyhow!(
-                                    "SOL is not present in Whirlpool pool: {}",
-                                    whirlpool_pool_pubkey
-                                ));
-                            };
+                            let (token_vault, sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &whirlpool.token_mint_a,
+                                whirlpool.token_vault_a,
+                                whirlpool.token_vault_b,
+                            );
 
                             let whirlpool_oracle = Pubkey::find_program_address(
                                 &[b"oracle", whirlpool_pool_pubkey.as_ref()],
@@ -515,11 +500,12 @@ pub async fn initialize_pool_data(
                                 tick_array_strings.iter().map(|s| s.as_str()).collect();
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == whirlpool.token_mint_a {
-                                (whirlpool.token_mint_a, whirlpool.token_mint_b)
-                            } else {
-                                (whirlpool.token_mint_b, whirlpool.token_mint_a)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &whirlpool.token_mint_a,
+                                whirlpool.token_mint_a,
+                                whirlpool.token_mint_b,
+                            );
                             
                             pool_data.add_whirlpool_pool(
                                 pool_address,
@@ -536,7 +522,7 @@ pub async fn initialize_pool_data(
                             info!("    Token mint A: {}", whirlpool.token_mint_a.to_string());
                             info!("    Token mint B: {}", whirlpool.token_mint_b.to_string());
                             info!("    Token vault: {}", token_vault.to_string());
-                            info!("    Sol vault: {}", sol_vault.to_string());
+                            info!("    Base vault: {}", sol_vault.to_string());
                             info!("    Oracle: {}", whirlpool_oracle.to_string());
 
                             for (i, array) in tick_array_strings.iter().enumerate() {
@@ -590,16 +576,12 @@ pub async fn initialize_pool_data(
                                 continue;
                             }
 
-                            let sol_mint = sol_mint();
-                            let (token_vault, sol_vault) = if sol_mint == raydium_clmm.token_mint_0
-                            {
-                                (raydium_clmm.token_vault_1, raydium_clmm.token_vault_0)
-                            } else if sol_mint == raydium_clmm.token_mint_1 {
-                                (raydium_clmm.token_vault_0, raydium_clmm.token_vault_1)
-                            } else {
-                                error!("SOL is not present in Raydium CLMM pool {}", pool_address);
-                                continue;
-                            };
+                            let (token_vault, sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &raydium_clmm.token_mint_0,
+                                raydium_clmm.token_vault_0,
+                                raydium_clmm.token_vault_1,
+                            );
 
                             let tick_array_pubkeys = get_tick_array_pubkeys(
                                 &Pubkey::from_str(pool_address)?,
@@ -618,11 +600,12 @@ pub async fn initialize_pool_data(
                                 tick_array_strings.iter().map(|s| s.as_str()).collect();
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == raydium_clmm.token_mint_0 {
-                                (raydium_clmm.token_mint_0, raydium_clmm.token_mint_1)
-                            } else {
-                                (raydium_clmm.token_mint_1, raydium_clmm.token_mint_0)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &raydium_clmm.token_mint_0,
+                                raydium_clmm.token_mint_0,
+                                raydium_clmm.token_mint_1,
+                            );
                             
                             pool_data.add_raydium_clmm_pool(
                                 pool_address,
@@ -646,7 +629,7 @@ pub async fn initialize_pool_data(
                                 raydium_clmm.token_mint_1.to_string()
                             );
                             info!("    Token vault: {}", token_vault.to_string());
-                            info!("    Sol vault: {}", sol_vault.to_string());
+                            info!("    Base vault: {}", sol_vault.to_string());
                             info!("    AMM config: {}", raydium_clmm.amm_config.to_string());
                             info!(
                                 "    Observation key: {}",
@@ -694,7 +677,7 @@ pub async fn initialize_pool_data(
                         ));
                     }
 
-                    match meteora_damm_cpi::Pool::deserialize_unchecked(&account.data) {
+                    match load_damm_pool(&account.data) {
                         Ok(pool) => {
                             if pool.token_a_mint != pool_data.mint
                                 && pool.token_b_mint != pool_data.mint
@@ -703,29 +686,19 @@ pub async fn initialize_pool_data(
                                     "Mint {} is not present in Meteora DAMM pool {}, skipping",
                                     pool_data.mint, meteora_damm_pool_pubkey
                                 );
-                                return Err(anyhow::anyhow!(
-                                    "Invalid Meteora DAMM pool: {}",
-                                    meteora_damm_pool_pubkey
-                                ));
-                            }
-
-                            let sol_mint = sol_mint();
-                            if pool.token_a_mint != sol_mint && pool.token_b_mint != sol_mint {
-                                error!(
-                                    "SOL is not present in Meteora DAMM pool {}",
-                                    meteora_damm_pool_pubkey
-                                );
-                                return Err(anyhow::anyhow!(
-                                    "SOL is not present in Meteora DAMM pool: {}",
-                                    meteora_damm_pool_pubkey
-                                ));
+                                return Err(DecodeError::MintMismatch {
+                                    mint: pool_data.mint,
+                                    pool: meteora_damm_pool_pubkey,
+                                }
+                                .into());
                             }
 
-                            let (x_vault, sol_vault) = if sol_mint == pool.token_a_mint {
-                                (pool.b_vault, pool.a_vault)
-                            } else {
-                                (pool.a_vault, pool.b_vault)
-                            };
+                            let (x_vault, sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &pool.token_a_mint,
+                                pool.a_vault,
+                                pool.b_vault,
+                            );
 
                             // Fetch vault accounts
                             let x_vault_data = rpc_client.get_account(&x_vault)?;
@@ -743,24 +716,27 @@ pub async fn initialize_pool_data(
                             let x_lp_mint = x_vault_obj.lp_mint;
                             let sol_lp_mint = sol_vault_obj.lp_mint;
 
-                            let (x_pool_lp, sol_pool_lp) = if sol_mint == pool.token_a_mint {
-                                (pool.b_vault_lp, pool.a_vault_lp)
-                            } else {
-                                (pool.a_vault_lp, pool.b_vault_lp)
-                            };
+                            let (x_pool_lp, sol_pool_lp) = token_and_base(
+                                &mint_pubkey,
+                                &pool.token_a_mint,
+                                pool.a_vault_lp,
+                                pool.b_vault_lp,
+                            );
 
-                            let (x_admin_fee, sol_admin_fee) = if sol_mint == pool.token_a_mint {
-                                (pool.admin_token_b_fee, pool.admin_token_a_fee)
-                            } else {
-                                (pool.admin_token_a_fee, pool.admin_token_b_fee)
-                            };
+                            let (x_admin_fee, sol_admin_fee) = token_and_base(
+                                &mint_pubkey,
+                                &pool.token_a_mint,
+                                pool.admin_token_a_fee,
+                                pool.admin_token_b_fee,
+                            );
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == pool.token_a_mint {
-                                (pool.token_a_mint, pool.token_b_mint)
-                            } else {
-                                (pool.token_b_mint, pool.token_a_mint)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &pool.token_a_mint,
+                                pool.token_a_mint,
+                                pool.token_b_mint,
+                            );
                             
                             pool_data.add_meteora_damm_pool(
                                 pool_address,
@@ -780,13 +756,13 @@ pub async fn initialize_pool_data(
 
                             info!("Meteora DAMM pool added: {}", pool_address);
                             info!("    Token X vault: {}", x_token_vault.to_string());
-                            info!("    SOL vault: {}", sol_token_vault.to_string());
+                            info!("    Base vault: {}", sol_token_vault.to_string());
                             info!("    Token X LP mint: {}", x_lp_mint.to_string());
-                            info!("    SOL LP mint: {}", sol_lp_mint.to_string());
+                            info!("    Base LP mint: {}", sol_lp_mint.to_string());
                             info!("    Token X pool LP: {}", x_pool_lp.to_string());
-                            info!("    SOL pool LP: {}", sol_pool_lp.to_string());
+                            info!("    Base pool LP: {}", sol_pool_lp.to_string());
                             info!("    Token X admin fee: {}", x_admin_fee.to_string());
-                            info!("    SOL admin fee: {}", sol_admin_fee.to_string());
+                            info!("    Base admin fee: {}", sol_admin_fee.to_string());
                             info!("");
                         }
                         Err(e) => {
@@ -840,23 +816,19 @@ pub async fn initialize_pool_data(
                                 meteora_damm_v2_info.quote_vault.to_string()
                             );
                             info!("");
-                            let token_x_vault = if sol_mint() == meteora_damm_v2_info.base_mint {
-                                meteora_damm_v2_info.quote_vault
-                            } else {
-                                meteora_damm_v2_info.base_vault
-                            };
-
-                            let token_sol_vault = if sol_mint() == meteora_damm_v2_info.base_mint {
-                                meteora_damm_v2_info.base_vault
-                            } else {
-                                meteora_damm_v2_info.quote_vault
-                            };
+                            let (token_x_vault, token_sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &meteora_damm_v2_info.base_mint,
+                                meteora_damm_v2_info.base_vault,
+                                meteora_damm_v2_info.quote_vault,
+                            );
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == meteora_damm_v2_info.base_mint {
-                                (meteora_damm_v2_info.base_mint, meteora_damm_v2_info.quote_mint)
-                            } else {
-                                (meteora_damm_v2_info.quote_mint, meteora_damm_v2_info.base_mint)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &meteora_damm_v2_info.base_mint,
+                                meteora_damm_v2_info.base_mint,
+                                meteora_damm_v2_info.quote_mint,
+                            );
                             
                             pool_data.add_meteora_damm_v2_pool(
                                 pool_address,
@@ -908,24 +880,20 @@ pub async fn initialize_pool_data(
                             info!("    Base vault: {}", solfi_info.base_vault.to_string());
                             info!("    Quote vault: {}", solfi_info.quote_vault.to_string());
 
-                            let token_x_vault = if sol_mint() == solfi_info.base_mint {
-                                solfi_info.quote_vault
-                            } else {
-                                solfi_info.base_vault
-                            };
-
-                            let token_sol_vault = if sol_mint() == solfi_info.base_mint {
-                                solfi_info.base_vault
-                            } else {
-                                solfi_info.quote_vault
-                            };
+                            let (token_x_vault, token_sol_vault) = token_and_base(
+                                &mint_pubkey,
+                                &solfi_info.base_mint,
+                                solfi_info.base_vault,
+                                solfi_info.quote_vault,
+                            );
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == solfi_info.base_mint {
-                                (solfi_info.base_mint, solfi_info.quote_mint)
-                            } else {
-                                (solfi_info.quote_mint, solfi_info.base_mint)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &solfi_info.base_mint,
+                                solfi_info.base_mint,
+                                solfi_info.quote_mint,
+                            );
                             
                             pool_data.add_solfi_pool(
                                 pool_address,
@@ -977,41 +945,31 @@ pub async fn initialize_pool_data(
                             info!("    Mint A: {}", vertigo_info.mint_a.to_string());
                             info!("    Mint B: {}", vertigo_info.mint_b.to_string());
 
-                            let base_mint = pool_data.mint.to_string();
-
-                            // Following the original loading pattern from user's code:
-                            let non_base_vault = if base_mint == vertigo_info.mint_a.to_string() {
-                                derive_vault_address(&vertigo_pool_pubkey, &vertigo_info.mint_b).0
-                            } else {
-                                derive_vault_address(&vertigo_pool_pubkey, &vertigo_info.mint_a).0
-                            };
-                            let base_vault = if base_mint == vertigo_info.mint_a.to_string() {
-                                derive_vault_address(&vertigo_pool_pubkey, &vertigo_info.mint_a).0
-                            } else {
-                                derive_vault_address(&vertigo_pool_pubkey, &vertigo_info.mint_b).0
-                            };
-
-                            // Map to transaction expected fields:
-                            // base_mint is our trading token, non-base should be SOL
-                            let token_x_vault = base_vault; // vault for our trading token
-                            let token_sol_vault = non_base_vault; // vault for SOL
+                            // Vertigo derives its vaults from the pool and mint instead of storing them
+                            let (token_x_vault, token_base_vault) = token_and_base(
+                                &mint_pubkey,
+                                &vertigo_info.mint_a,
+                                derive_vault_address(&vertigo_pool_pubkey, &vertigo_info.mint_a).0,
+                                derive_vault_address(&vertigo_pool_pubkey, &vertigo_info.mint_b).0,
+                            );
 
                             info!("    Token X Vault: {}", token_x_vault.to_string());
-                            info!("    Token SOL Vault: {}", token_sol_vault.to_string());
+                            info!("    Token Base Vault: {}", token_base_vault.to_string());
                             info!("");
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == vertigo_info.mint_a {
-                                (vertigo_info.mint_a, vertigo_info.mint_b)
-                            } else {
-                                (vertigo_info.mint_b, vertigo_info.mint_a)
-                            };
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &vertigo_info.mint_a,
+                                vertigo_info.mint_a,
+                                vertigo_info.mint_b,
+                            );
                             
                             pool_data.add_vertigo_pool(
                                 pool_address,
                                 &vertigo_info.pool.to_string(),
                                 &token_x_vault.to_string(),
-                                &token_sol_vault.to_string(),
+                                &token_base_vault.to_string(),
                                 &token_mint.to_string(),
                                 &base_mint.to_string(),
                             )?;
@@ -1053,7 +1011,7 @@ pub async fn initialize_pool_data(
                     }
 
                     match HeavenPoolState::parse(&account.data) {
-                        Some(heaven_info) => {
+                        Ok(heaven_info) => {
                             info!("Heaven pool added: {}", pool_address);
                             info!("    Mint A: {}", heaven_info.mint_a.to_string());
                             info!("    Mint B: {}", heaven_info.mint_b.to_string());
@@ -1064,31 +1022,20 @@ pub async fn initialize_pool_data(
                             info!("    Reserve B: {}", heaven_info.reserve_b);
 
                             // Determine which vault corresponds to token and base
-                            let (token_x_vault, token_base_vault) = 
-                                if mint_pubkey == heaven_info.mint_a {
-                                    (heaven_info.vault_a, heaven_info.vault_b)
-                                } else {
-                                    (heaven_info.vault_b, heaven_info.vault_a)
-                                };
+                            let (token_x_vault, token_base_vault) = token_and_base(
+                                &mint_pubkey,
+                                &heaven_info.mint_a,
+                                heaven_info.vault_a,
+                                heaven_info.vault_b,
+                            );
 
                             // Determine token_mint and base_mint
-                            let (token_mint, base_mint) = if mint_pubkey == heaven_info.mint_a {
-                                (heaven_info.mint_a, heaven_info.mint_b)
-                            } else {
-                                (heaven_info.mint_b, heaven_info.mint_a)
-                            };
-
-                            // Validate that the base mint is either SOL or USDC
-                            let usdc_mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
-                            if base_mint != sol_mint() && base_mint != usdc_mint {
-                                error!(
-                                    "Invalid Heaven pool: Expected SOL or USDC as base mint, but found {}",
-                                    base_mint
-                                );
-                                return Err(anyhow::anyhow!(
-                                    "Invalid Heaven pool: Expected SOL or USDC as base mint"
-                                ));
-                            }
+                            let (token_mint, base_mint) = token_and_base(
+                                &mint_pubkey,
+                                &heaven_info.mint_a,
+                                heaven_info.mint_a,
+                                heaven_info.mint_b,
+                            );
 
                             // Determine token program - check if either mint is Token-2022
                             let token_2022_program_id =
@@ -1110,10 +1057,10 @@ pub async fn initialize_pool_data(
                             
                             info!("    Initialized Heaven pool: {}\n", heaven_pool_pubkey);
                         }
-                        None => {
+                        Err(e) => {
                             error!(
-                                "Error parsing Heaven pool data from pool {}",
-                                heaven_pool_pubkey
+                                "Error parsing Heaven pool data from pool {}: {:?}",
+                                heaven_pool_pubkey, e
                             );
                             return Err(anyhow::anyhow!("Failed to parse Heaven pool data"));
                         }
@@ -1130,5 +1077,33 @@ pub async fn initialize_pool_data(
         }
     }
 
+    refresh_liquidity(rpc_client.as_ref(), &mut pool_data);
+
     Ok(pool_data)
 }
+
+/// Reads the base token balance of every pool. Pools whose vault can't be read are left out.
+pub fn refresh_liquidity(rpc_client: &dyn AccountSource, pool_data: &mut MintPoolData) {
+    let pools = pool_data.pools();
+    for chunk in pools.chunks(100) {
+        let vaults: Vec<Pubkey> = chunk.iter().map(|p| p.base_vault).collect();
+        let accounts = match rpc_client.get_multiple_accounts(&vaults) {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                error!("Error fetching pool vault balances: {:?}", e);
+                continue;
+            }
+        };
+        for (pool, account) in chunk.iter().zip(accounts) {
+            if let Some(amount) = account.as_ref().and_then(token_account_amount) {
+                pool_data.liquidity.insert(pool.pool, amount);
+            }
+        }
+    }
+}
+
+/// Token and Token 2022 accounts both store the amount at offset 64.
+pub fn token_account_amount(account: &Account) -> Option<u64> {
+    let bytes = account.data.get(64..72)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
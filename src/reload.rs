@@ -0,0 +1,125 @@
+use crate::config::{MintConfig, RoutingConfig};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tracing::warn;
+
+/// How often the config file is checked for changes when `reload_interval` is unset.
+pub const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What has to happen to the running mint tasks to go from one routing config to another.
+#[derive(Debug, Default)]
+pub struct RoutingChanges {
+    pub added: Vec<MintConfig>,
+    pub removed: Vec<String>,
+    /// Mints whose pool lists or lookup tables changed. Their pools are loaded again.
+    pub reinitialized: Vec<MintConfig>,
+    /// Mints where only `process_delay`, `routes` or `use_tpu` changed. Their pool data is kept.
+    pub updated: Vec<MintConfig>,
+}
+
+impl RoutingChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.reinitialized.is_empty()
+            && self.updated.is_empty()
+    }
+}
+
+pub fn diff_routing(current: &RoutingConfig, new: &RoutingConfig) -> RoutingChanges {
+    let mut changes = RoutingChanges::default();
+
+    for new_mint in &new.mint_config_list {
+        match current
+            .mint_config_list
+            .iter()
+            .find(|m| m.mint == new_mint.mint)
+        {
+            None => changes.added.push(new_mint.clone()),
+            Some(old_mint) if old_mint == new_mint => {}
+            Some(old_mint) => {
+                let mut old_pools = old_mint.clone();
+                old_pools.process_delay = new_mint.process_delay;
+                old_pools.routes = new_mint.routes.clone();
+                old_pools.use_tpu = new_mint.use_tpu;
+                if old_pools == *new_mint {
+                    changes.updated.push(new_mint.clone());
+                } else {
+                    changes.reinitialized.push(new_mint.clone());
+                }
+            }
+        }
+    }
+
+    for old_mint in &current.mint_config_list {
+        if !new.mint_config_list.iter().any(|m| m.mint == old_mint.mint) {
+            changes.removed.push(old_mint.mint.clone());
+        }
+    }
+
+    changes
+}
+
+/// Fires on SIGHUP and, when a poll interval is set, whenever the config file's
+/// modification time changes.
+pub struct ReloadTrigger {
+    path: PathBuf,
+    poll_interval: Option<Duration>,
+    modified: Option<SystemTime>,
+    hangup: Signal,
+}
+
+impl ReloadTrigger {
+    pub fn new(path: impl Into<PathBuf>, poll_interval: Option<Duration>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let modified = modified_time(&path);
+        Ok(Self {
+            path,
+            poll_interval,
+            modified,
+            hangup: signal(SignalKind::hangup())?,
+        })
+    }
+
+    /// Completes when the config should be reloaded.
+    pub async fn next(&mut self) {
+        let poll_interval = self.poll_interval;
+        loop {
+            let poll = async {
+                match poll_interval {
+                    Some(interval) => tokio::time::sleep(interval).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                _ = self.hangup.recv() => {
+                    self.modified = modified_time(&self.path);
+                    return;
+                }
+                _ = poll => {
+                    let modified = modified_time(&self.path);
+                    if modified != self.modified {
+                        self.modified = modified;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    match std::fs::metadata(path).and_then(|m| m.modified()) {
+        Ok(modified) => Some(modified),
+        Err(e) => {
+            warn!(
+                "Failed to read modification time of {}: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
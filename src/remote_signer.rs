@@ -0,0 +1,140 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime, RuntimeFlavor};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize)]
+struct PubkeyResponse {
+    pubkey: String,
+}
+
+#[derive(Serialize)]
+struct SignRequest<'a> {
+    pubkey: &'a str,
+    /// Base64 of the serialized message.
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    /// Base58 signature.
+    signature: String,
+}
+
+/// Signs through an HTTP signing service, so the key never has to be on this host.
+///
+/// The service answers `GET {url}/pubkey` with `{"pubkey": "<base58>"}` and
+/// `POST {url}/sign` with `{"pubkey": "<base58>", "message": "<base64>"}` with
+/// `{"signature": "<base58>"}`. Every signature is verified before it is used.
+pub struct RemoteSigner {
+    url: String,
+    pubkey: Pubkey,
+    client: reqwest::Client,
+    /// Requests run on their own runtime, as signing is called from both blocking and async code.
+    runtime: Option<Runtime>,
+}
+
+impl RemoteSigner {
+    /// Connects to the service at `url` and asks it for the public key it signs for.
+    pub fn connect(url: &str) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("remote-signer")
+            .enable_all()
+            .build()?;
+        let mut signer = Self {
+            url: url.trim_end_matches('/').to_string(),
+            pubkey: Pubkey::default(),
+            client,
+            runtime: Some(runtime),
+        };
+        let response: PubkeyResponse = signer
+            .run(signer.client.get(format!("{}/pubkey", signer.url)))
+            .map_err(|e| anyhow::anyhow!("Failed to reach signer {}: {}", url, e))?;
+        signer.pubkey = Pubkey::from_str(&response.pubkey)?;
+        Ok(signer)
+    }
+
+    /// Sends a request and decodes its JSON answer. On a multi-threaded runtime the worker hands
+    /// its other tasks off while it waits; a current-thread runtime can't, so the request runs
+    /// on a scoped thread there.
+    fn run<T: DeserializeOwned + Send>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, SignerError> {
+        let runtime = self
+            .runtime
+            .as_ref()
+            .expect("runtime is only taken on drop");
+        let send = || {
+            runtime.block_on(async {
+                let response = request.send().await?.error_for_status()?;
+                response.json::<T>().await
+            })
+        };
+        let result = match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+            Ok(RuntimeFlavor::CurrentThread) => {
+                std::thread::scope(|scope| scope.spawn(send).join())
+            }
+            Ok(_) => Ok(tokio::task::block_in_place(send)),
+            Err(_) => Ok(send()),
+        };
+        match result {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(e)) => Err(SignerError::Connection(e.to_string())),
+            Err(_) => Err(SignerError::Custom("Signer request panicked".to_string())),
+        }
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let pubkey = self.pubkey.to_string();
+        let request = SignRequest {
+            pubkey: &pubkey,
+            message: STANDARD.encode(message),
+        };
+        let response: SignResponse = self.run(
+            self.client
+                .post(format!("{}/sign", self.url))
+                .json(&request),
+        )?;
+        let signature = Signature::from_str(&response.signature)
+            .map_err(|e| SignerError::Protocol(format!("Invalid signature: {}", e)))?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(SignerError::Protocol(format!(
+                "Signer returned a signature that does not verify for {}",
+                self.pubkey
+            )));
+        }
+        Ok(signature)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for RemoteSigner {
+    fn drop(&mut self) {
+        // Dropping a runtime blocks, which is not allowed on an async runtime thread
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
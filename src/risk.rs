@@ -0,0 +1,493 @@
+use crate::config::RiskConfig;
+use crate::constants::SOL_MINT;
+use crate::ledger::{Ledger, LedgerEntry};
+use crate::rpc::AccountSource;
+use crate::rpc_pool::RpcPool;
+use crate::supervisor::Shutdown;
+use solana_client::nonce_utils;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{
+    UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance,
+};
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::{error, info, warn};
+
+/// Sent transactions that are still unknown after this long are treated as
+/// expired: their blockhash is no longer valid, so they can no longer cost fees.
+/// Durable nonce transactions don't expire, they are followed until their nonce advances.
+const PENDING_EXPIRY: Duration = Duration::from_secs(90);
+const SETTLEMENT_INTERVAL: Duration = Duration::from_secs(2);
+/// Upper bound on signatures accepted by `getSignatureStatuses`.
+const MAX_STATUS_BATCH: usize = 256;
+/// Upper bound on accounts accepted by `getMultipleAccounts`.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum HaltReason {
+    #[error("kill switch engaged")]
+    KillSwitch,
+    #[error("kill switch file {0} is present")]
+    KillSwitchFile(String),
+    #[error("balance {balance} of wallet {wallet} is below the floor of {floor} lamports")]
+    BalanceFloor {
+        wallet: Pubkey,
+        balance: u64,
+        floor: u64,
+    },
+    #[error(
+        "net loss of {loss} lamports over the last {window_secs}s exceeds the limit of {limit}"
+    )]
+    LossLimit {
+        loss: u64,
+        limit: u64,
+        window_secs: u64,
+    },
+}
+
+#[derive(Clone)]
+struct PendingSend {
+    signature: Signature,
+    mint: String,
+    wallet: Pubkey,
+    sent_at: Instant,
+    /// Nonce account and value of a durable nonce transaction.
+    nonce: Option<(Pubkey, Hash)>,
+}
+
+struct SettledSend {
+    settled_at: Instant,
+    fee: u64,
+    pnl: i64,
+}
+
+#[derive(Default)]
+struct RiskState {
+    pending: VecDeque<PendingSend>,
+    settled: VecDeque<SettledSend>,
+    last_halt: Option<String>,
+}
+
+/// Follows every send until it lands or expires. When limits are configured it
+/// also settles what each send cost and halts sending when losses or the balance
+/// of any wallet cross them.
+pub struct RiskManager {
+    config: Option<RiskConfig>,
+    rpc_pool: Arc<RpcPool>,
+    wallets: Vec<Pubkey>,
+    ledger: Arc<Ledger>,
+    kill_switch: AtomicBool,
+    /// SOL balance of each wallet, in the order of `wallets`.
+    wallet_balances: Vec<AtomicU64>,
+    state: Mutex<RiskState>,
+}
+
+impl RiskManager {
+    pub fn new(
+        config: Option<RiskConfig>,
+        rpc_pool: Arc<RpcPool>,
+        wallets: Vec<Pubkey>,
+        ledger: Arc<Ledger>,
+    ) -> anyhow::Result<Self> {
+        let wallet_balances = wallets
+            .iter()
+            .map(|wallet| Ok(AtomicU64::new(rpc_pool.primary().get_balance(wallet)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            config: config.filter(|c| c.enabled),
+            rpc_pool,
+            wallets,
+            ledger,
+            kill_switch: AtomicBool::new(false),
+            wallet_balances,
+            state: Mutex::new(RiskState::default()),
+        })
+    }
+
+    pub fn limits_enabled(&self) -> bool {
+        self.config.is_some()
+    }
+
+    fn loss_window(&self) -> Duration {
+        let window_secs = self.config.as_ref().and_then(|c| c.loss_window_secs);
+        Duration::from_secs(window_secs.unwrap_or(86_400))
+    }
+
+    pub fn engage_kill_switch(&self) {
+        self.kill_switch.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns why sending is currently not allowed, if it isn't.
+    pub fn check(&self) -> Result<(), HaltReason> {
+        let result = self.evaluate();
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let halt = result.as_ref().err().map(|reason| reason.to_string());
+        if halt != state.last_halt {
+            match &halt {
+                Some(reason) => {
+                    error!("Sending halted: {}", reason);
+                    self.ledger.record(LedgerEntry::TradingHalted {
+                        reason: reason.clone(),
+                    });
+                }
+                None => {
+                    info!("Sending resumed");
+                    self.ledger.record(LedgerEntry::TradingResumed);
+                }
+            }
+            state.last_halt = halt;
+        }
+
+        result
+    }
+
+    fn evaluate(&self) -> Result<(), HaltReason> {
+        if self.kill_switch.load(Ordering::SeqCst) {
+            return Err(HaltReason::KillSwitch);
+        }
+
+        let Some(config) = &self.config else {
+            return Ok(());
+        };
+
+        if let Some(path) = &config.kill_switch_file {
+            if Path::new(path).exists() {
+                return Err(HaltReason::KillSwitchFile(path.clone()));
+            }
+        }
+
+        for (wallet, balance) in self.wallet_balances() {
+            if balance < config.min_wallet_balance {
+                return Err(HaltReason::BalanceFloor {
+                    wallet,
+                    balance,
+                    floor: config.min_wallet_balance,
+                });
+            }
+        }
+
+        let net_pnl = self.net_pnl();
+        if net_pnl < 0 && net_pnl.unsigned_abs() > config.max_net_loss {
+            return Err(HaltReason::LossLimit {
+                loss: net_pnl.unsigned_abs(),
+                limit: config.max_net_loss,
+                window_secs: self.loss_window().as_secs(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Sends settled within the loss window. Older ones are dropped.
+    fn settled_in_window(&self) -> MutexGuard<'_, RiskState> {
+        let window = self.loss_window();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while state
+            .settled
+            .front()
+            .is_some_and(|s| s.settled_at.elapsed() > window)
+        {
+            state.settled.pop_front();
+        }
+        state
+    }
+
+    /// Net wallet change over the loss window, fees included.
+    pub fn net_pnl(&self) -> i64 {
+        self.settled_in_window().settled.iter().map(|s| s.pnl).sum()
+    }
+
+    /// Fees paid over the loss window.
+    pub fn fees_paid(&self) -> u64 {
+        self.settled_in_window().settled.iter().map(|s| s.fee).sum()
+    }
+
+    /// Last known SOL balance of every wallet.
+    pub fn wallet_balances(&self) -> Vec<(Pubkey, u64)> {
+        self.wallets
+            .iter()
+            .zip(&self.wallet_balances)
+            .map(|(wallet, balance)| (*wallet, balance.load(Ordering::SeqCst)))
+            .collect()
+    }
+
+    /// Follows a send of `mint` signed by `wallet`. A send made with a durable `nonce` stays
+    /// pending until the nonce advances, as it can land any time before that.
+    pub fn track(
+        &self,
+        signature: Signature,
+        mint: &str,
+        wallet: Pubkey,
+        nonce: Option<(Pubkey, Hash)>,
+    ) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.pending.push_back(PendingSend {
+            signature,
+            mint: mint.to_string(),
+            wallet,
+            sent_at: Instant::now(),
+            nonce,
+        });
+    }
+
+    pub fn pending_count(&self) -> usize {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.pending.len()
+    }
+
+    /// Checks every pending send, `MAX_STATUS_BATCH` signatures per request.
+    fn settle_pending(&self) -> anyhow::Result<()> {
+        let pending: Vec<PendingSend> = {
+            let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.pending.iter().cloned().collect()
+        };
+        for batch in pending.chunks(MAX_STATUS_BATCH) {
+            self.settle_batch(batch)?;
+        }
+        Ok(())
+    }
+
+    fn settle_batch(&self, batch: &[PendingSend]) -> anyhow::Result<()> {
+        let signatures: Vec<Signature> = batch.iter().map(|p| p.signature).collect();
+        let statuses = self
+            .rpc_pool
+            .primary()
+            .get_signature_statuses(&signatures)?
+            .value;
+        let unknown_nonces: Vec<(Pubkey, Hash)> = batch
+            .iter()
+            .zip(&statuses)
+            .filter(|(_, status)| status.is_none())
+            .filter_map(|(p, _)| p.nonce)
+            .collect();
+        let advanced = self.advanced_nonces(&unknown_nonces)?;
+
+        let mut done = Vec::new();
+        for (pending, status) in batch.iter().zip(statuses) {
+            let PendingSend {
+                signature,
+                mint,
+                wallet,
+                sent_at,
+                nonce,
+            } = pending;
+            match status {
+                Some(status) if status.satisfies_commitment(CommitmentConfig::confirmed()) => {
+                    if !self.limits_enabled() {
+                        done.push(*signature);
+                        continue;
+                    }
+                    match self.fetch_outcome(signature, wallet) {
+                        Ok((fee, pnl)) => {
+                            self.ledger.record(LedgerEntry::TransactionSettled {
+                                signature: signature.to_string(),
+                                mint: mint.clone(),
+                                wallet: wallet.to_string(),
+                                success: status.err.is_none(),
+                                fee,
+                                pnl,
+                            });
+                            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                            state.settled.push_back(SettledSend {
+                                settled_at: Instant::now(),
+                                fee,
+                                pnl,
+                            });
+                            done.push(*signature);
+                        }
+                        Err(e) => {
+                            warn!("Failed to fetch transaction {}: {:?}", signature, e);
+                        }
+                    }
+                }
+                // Advancing the nonce either landed the send or made it invalid
+                _ if nonce.is_some_and(|nonce| advanced.contains(&nonce)) => done.push(*signature),
+                _ if nonce.is_none() && sent_at.elapsed() > PENDING_EXPIRY => done.push(*signature),
+                _ => {}
+            }
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.pending.retain(|p| !done.contains(&p.signature));
+        Ok(())
+    }
+
+    /// The nonces of `used` that the chain shows advanced. A nonce account that is gone or
+    /// no longer initialized can't be used any more either.
+    fn advanced_nonces(&self, used: &[(Pubkey, Hash)]) -> anyhow::Result<HashSet<(Pubkey, Hash)>> {
+        if used.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let addresses: Vec<Pubkey> = used.iter().map(|(address, _)| *address).collect();
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            accounts.extend(self.rpc_pool.get_multiple_accounts(chunk)?);
+        }
+        Ok(used
+            .iter()
+            .zip(accounts)
+            .filter(|((_, nonce), account)| {
+                match account
+                    .as_ref()
+                    .and_then(|account| nonce_utils::data_from_account(account).ok())
+                {
+                    Some(data) => data.blockhash() != *nonce,
+                    None => true,
+                }
+            })
+            .map(|(used, _)| *used)
+            .collect())
+    }
+
+    fn fetch_outcome(&self, signature: &Signature, wallet: &Pubkey) -> anyhow::Result<(u64, i64)> {
+        let tx = self.rpc_pool.primary().get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let account_keys = tx
+            .transaction
+            .transaction
+            .decode()
+            .map(|decoded| decoded.message.static_account_keys().to_vec())
+            .ok_or_else(|| anyhow::anyhow!("Failed to decode transaction {}", signature))?;
+        let meta = tx
+            .transaction
+            .meta
+            .ok_or_else(|| anyhow::anyhow!("Transaction {} has no status meta", signature))?;
+
+        Ok((meta.fee, wallet_pnl(&meta, &account_keys, wallet)))
+    }
+
+    /// Keeps settling until every tracked send has landed or expired, or the timeout passes.
+    pub async fn wait_for_pending(&self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while self.pending_count() > 0 && Instant::now() < deadline {
+            info!(
+                "Waiting for {} sent transactions to confirm",
+                self.pending_count()
+            );
+            if let Err(e) = self.settle_pending() {
+                error!("Failed to settle sent transactions: {:?}", e);
+            }
+            tokio::time::sleep(SETTLEMENT_INTERVAL).await;
+        }
+
+        let remaining = self.pending_count();
+        if remaining > 0 {
+            warn!("Gave up waiting on {} unconfirmed transactions", remaining);
+        }
+    }
+
+    fn refresh_wallet_balances(&self) -> anyhow::Result<()> {
+        for (wallet, balance) in self.wallets.iter().zip(&self.wallet_balances) {
+            balance.store(
+                self.rpc_pool.primary().get_balance(wallet)?,
+                Ordering::SeqCst,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Change in the SOL plus WSOL holdings of `wallet` caused by a transaction. A separate fee
+/// payer, always account 0, is counted too, so fees weigh on the PnL whoever pays them.
+pub fn wallet_pnl(meta: &UiTransactionStatusMeta, account_keys: &[Pubkey], wallet: &Pubkey) -> i64 {
+    let mut owners = vec![*wallet];
+    owners.extend(
+        account_keys
+            .first()
+            .filter(|fee_payer| *fee_payer != wallet),
+    );
+
+    let sol_delta: i64 = owners
+        .iter()
+        .filter_map(|owner| account_keys.iter().position(|key| key == owner))
+        .filter_map(|i| {
+            Some(*meta.post_balances.get(i)? as i64 - *meta.pre_balances.get(i)? as i64)
+        })
+        .sum();
+
+    let owners: Vec<_> = owners
+        .iter()
+        .map(|owner| OptionSerializer::Some(owner.to_string()))
+        .collect();
+    let wsol_amount = |balances: Option<&Vec<UiTransactionTokenBalance>>| -> i64 {
+        balances
+            .into_iter()
+            .flatten()
+            .filter(|b| b.mint == SOL_MINT && owners.contains(&b.owner))
+            .filter_map(|b| b.ui_token_amount.amount.parse::<i64>().ok())
+            .sum()
+    };
+    let wsol_delta = wsol_amount(Option::from(meta.post_token_balances.as_ref()))
+        - wsol_amount(Option::from(meta.pre_token_balances.as_ref()));
+
+    sol_delta + wsol_delta
+}
+
+pub async fn settlement_loop(risk: Arc<RiskManager>, mut shutdown: Shutdown) {
+    while !shutdown.is_triggered() {
+        if let Err(e) = risk.settle_pending() {
+            error!("Failed to settle sent transactions: {:?}", e);
+        }
+        shutdown.sleep(SETTLEMENT_INTERVAL).await;
+    }
+}
+
+pub async fn balance_monitor_loop(risk: Arc<RiskManager>, mut shutdown: Shutdown) {
+    let check_interval = risk.config.as_ref().and_then(|c| c.balance_check_interval);
+    let interval = Duration::from_millis(check_interval.unwrap_or(10_000));
+    while !shutdown.is_triggered() {
+        if let Err(e) = risk.refresh_wallet_balances() {
+            error!("Failed to refresh wallet balances: {:?}", e);
+        }
+        for (wallet, balance) in risk.wallet_balances() {
+            info!("Risk: wallet {} balance {} lamports", wallet, balance);
+        }
+        info!(
+            "Risk: net PnL {} lamports, fees {} lamports over the last {}s, {} sends pending",
+            risk.net_pnl(),
+            risk.fees_paid(),
+            risk.loss_window().as_secs(),
+            risk.pending_count()
+        );
+        shutdown.sleep(interval).await;
+    }
+}
+
+/// Engages the kill switch on SIGUSR1. It stays engaged until the bot restarts.
+pub async fn kill_signal_listener(risk: Arc<RiskManager>, mut shutdown: Shutdown) {
+    let mut user_signal = match signal(SignalKind::user_defined1()) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Failed to install SIGUSR1 handler: {:?}", e);
+            return;
+        }
+    };
+    loop {
+        tokio::select! {
+            received = user_signal.recv() => {
+                if received.is_none() {
+                    return;
+                }
+                warn!("SIGUSR1 received, engaging kill switch");
+                risk.engage_kill_switch();
+            }
+            _ = shutdown.wait() => return,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_client::rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY;
+use solana_client::rpc_request::RpcError;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+use std::time::Duration;
+
+/// Where pool, mint and vault accounts are read from. `RpcClient` reads them from the chain;
+/// tests seed an in-memory source instead.
+pub trait AccountSource: Send + Sync {
+    fn get_account(&self, pubkey: &Pubkey) -> anyhow::Result<Account>;
+
+    /// `None` for every account that doesn't exist, in the order of `pubkeys`.
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>>;
+}
+
+/// Why a transaction could not be sent, classified so the sender can react to each kind.
+#[derive(Debug, thiserror::Error)]
+pub enum SendError {
+    #[error("rate limited by the endpoint")]
+    RateLimited,
+    #[error("blockhash not found")]
+    BlockhashNotFound,
+    #[error("node is unhealthy: {0}")]
+    NodeUnhealthy(String),
+    #[error("connection error: {0}")]
+    Connection(String),
+    /// Not sent because the endpoint's own send rate limit is used up.
+    #[error("send rate limit reached")]
+    OverLimit,
+    /// Not sent because an earlier error paused the endpoint.
+    #[error("endpoint paused for another {}ms", .0.as_millis())]
+    Paused(Duration),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl SendError {
+    /// Errors that come from this bot's own limits rather than from the endpoint.
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, SendError::OverLimit | SendError::Paused(_))
+    }
+}
+
+impl From<ClientError> for SendError {
+    fn from(error: ClientError) -> Self {
+        if let Some(TransactionError::BlockhashNotFound) = error.kind.get_transaction_error() {
+            return SendError::BlockhashNotFound;
+        }
+        match &error.kind {
+            ClientErrorKind::Reqwest(e) if e.status().is_some_and(|s| s.as_u16() == 429) => {
+                SendError::RateLimited
+            }
+            ClientErrorKind::Reqwest(e) if e.is_connect() || e.is_timeout() => {
+                SendError::Connection(e.to_string())
+            }
+            ClientErrorKind::Io(e) => SendError::Connection(e.to_string()),
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. }) => {
+                if *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY {
+                    SendError::NodeUnhealthy(message.clone())
+                } else if *code == 429 || message.to_lowercase().contains("too many requests") {
+                    SendError::RateLimited
+                } else if message.contains("Blockhash not found") {
+                    SendError::BlockhashNotFound
+                } else {
+                    SendError::Other(error.to_string())
+                }
+            }
+            _ => SendError::Other(error.to_string()),
+        }
+    }
+}
+
+/// Where arbitrage transactions are sent.
+pub trait TxSender: Send + Sync {
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        max_retries: u64,
+    ) -> Result<Signature, SendError>;
+}
+
+impl AccountSource for RpcClient {
+    fn get_account(&self, pubkey: &Pubkey) -> anyhow::Result<Account> {
+        Ok(RpcClient::get_account(self, pubkey)?)
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        Ok(RpcClient::get_multiple_accounts(self, pubkeys)?)
+    }
+}
+
+impl TxSender for RpcClient {
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        max_retries: u64,
+    ) -> Result<Signature, SendError> {
+        Ok(self.send_transaction_with_config(
+            tx,
+            RpcSendTransactionConfig {
+                skip_preflight: true,
+                max_retries: Some(max_retries as usize),
+                preflight_commitment: Some(CommitmentLevel::Confirmed),
+                ..Default::default()
+            },
+        )?)
+    }
+}
@@ -0,0 +1,344 @@
+use crate::config::RpcConfig;
+use crate::rpc::{AccountSource, SendError, TxSender};
+use crate::supervisor::Shutdown;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+const DEFAULT_MAX_SLOT_LAG: u64 = 25;
+const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// Weight of the latest check in the latency and error rate averages.
+const SMOOTHING: f64 = 0.2;
+/// Latency at which the latency factor of the score is one half.
+const REFERENCE_LATENCY_MS: f64 = 100.0;
+/// How much better another endpoint has to score before it replaces the primary.
+const SWITCH_MARGIN: f64 = 1.1;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EndpointHealth {
+    /// Slot of the last successful check. `None` until one succeeds.
+    pub slot: Option<u64>,
+    /// Average request latency in milliseconds.
+    pub latency_ms: f64,
+    /// Average share of failed requests, 0 to 1.
+    pub error_rate: f64,
+    /// Whether the last check failed.
+    pub failing: bool,
+}
+
+impl EndpointHealth {
+    pub fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        // The first answer sets the average
+        self.latency_ms = if self.slot.is_none() {
+            latency_ms
+        } else {
+            SMOOTHING * latency_ms + (1.0 - SMOOTHING) * self.latency_ms
+        };
+        self.error_rate *= 1.0 - SMOOTHING;
+        self.failing = false;
+    }
+
+    pub fn record_failure(&mut self) {
+        self.error_rate = SMOOTHING + (1.0 - SMOOTHING) * self.error_rate;
+        self.failing = true;
+    }
+
+    /// 0 to 100. Endpoints whose last check failed, or that are more than `max_slot_lag` slots
+    /// behind `best_slot`, score 0.
+    pub fn score(&self, best_slot: u64, max_slot_lag: u64) -> f64 {
+        let Some(slot) = self.slot else {
+            return 0.0;
+        };
+        let lag = best_slot.saturating_sub(slot);
+        if self.failing || lag > max_slot_lag {
+            return 0.0;
+        }
+        let lag_factor = 1.0 - lag as f64 / (max_slot_lag + 1) as f64;
+        let latency_factor = REFERENCE_LATENCY_MS / (REFERENCE_LATENCY_MS + self.latency_ms);
+        100.0 * (1.0 - self.error_rate) * lag_factor * latency_factor
+    }
+}
+
+/// The endpoint to read from: the best scoring one, unless `current` scores within the switch
+/// margin of it.
+pub fn pick_primary(scores: &[f64], current: usize) -> usize {
+    let Some((best, best_score)) = scores
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+    else {
+        return current;
+    };
+    let current_score = scores.get(current).copied().unwrap_or(0.0);
+    if best_score > current_score * SWITCH_MARGIN || current_score == 0.0 && best_score > 0.0 {
+        best
+    } else {
+        current
+    }
+}
+
+struct Endpoint {
+    /// Host of the URL. Paths and query strings often hold API keys, so they are left out.
+    label: String,
+    client: Arc<RpcClient>,
+    health: Mutex<EndpointHealth>,
+}
+
+/// Read endpoints with health checks. Reads go to the primary, the endpoint that scored best on
+/// slot lag, error rate and latency.
+pub struct RpcPool {
+    endpoints: Vec<Endpoint>,
+    primary: AtomicUsize,
+    max_slot_lag: u64,
+    health_check_interval: Duration,
+    metrics_path: Option<PathBuf>,
+}
+
+impl RpcPool {
+    pub fn new(config: &RpcConfig) -> Self {
+        let urls = std::iter::once(&config.url).chain(config.read_urls.iter().flatten());
+        let mut pool = Self::with_clients(
+            urls.map(|url| (endpoint_label(url), Arc::new(RpcClient::new(url.clone()))))
+                .collect(),
+            config.max_slot_lag.unwrap_or(DEFAULT_MAX_SLOT_LAG),
+        );
+        pool.health_check_interval = config
+            .health_check_interval
+            .map_or(DEFAULT_HEALTH_CHECK_INTERVAL, Duration::from_millis);
+        pool.metrics_path = config.metrics_path.as_ref().map(PathBuf::from);
+        pool
+    }
+
+    /// A pool of labelled clients. The first one is primary until a health check says otherwise.
+    pub fn with_clients(clients: Vec<(String, Arc<RpcClient>)>, max_slot_lag: u64) -> Self {
+        Self {
+            endpoints: clients
+                .into_iter()
+                .map(|(label, client)| Endpoint {
+                    label,
+                    client,
+                    health: Mutex::new(EndpointHealth::default()),
+                })
+                .collect(),
+            primary: AtomicUsize::new(0),
+            max_slot_lag,
+            health_check_interval: DEFAULT_HEALTH_CHECK_INTERVAL,
+            metrics_path: None,
+        }
+    }
+
+    /// The client reads should go to.
+    pub fn primary(&self) -> &RpcClient {
+        &self.endpoints[self.primary.load(Ordering::Relaxed)].client
+    }
+
+    /// Every client, best scoring first, for callers that try them in turn.
+    pub fn clients_by_health(&self) -> Vec<Arc<RpcClient>> {
+        let scores = self.scores();
+        let mut order: Vec<usize> = (0..self.endpoints.len()).collect();
+        let primary = self.primary.load(Ordering::Relaxed);
+        order.sort_by(|a, b| {
+            (*b == primary)
+                .cmp(&(*a == primary))
+                .then(scores[*b].total_cmp(&scores[*a]))
+        });
+        order
+            .into_iter()
+            .map(|i| self.endpoints[i].client.clone())
+            .collect()
+    }
+
+    /// Checks every endpoint at once and moves reads to the healthiest.
+    pub fn check(&self) {
+        std::thread::scope(|scope| {
+            for endpoint in &self.endpoints {
+                scope.spawn(move || {
+                    let started = Instant::now();
+                    let result = endpoint.client.get_slot();
+                    let mut health = endpoint.health.lock().unwrap_or_else(|e| e.into_inner());
+                    match result {
+                        Ok(slot) => {
+                            health.record_success(started.elapsed());
+                            health.slot = Some(slot);
+                        }
+                        Err(e) => {
+                            warn!("Health check of RPC {} failed: {}", endpoint.label, e);
+                            health.record_failure();
+                        }
+                    }
+                });
+            }
+        });
+
+        let scores = self.scores();
+        let current = self.primary.load(Ordering::Relaxed);
+        let primary = pick_primary(&scores, current);
+        if primary != current {
+            warn!(
+                "Primary RPC changed from {} (score {:.1}) to {} (score {:.1})",
+                self.endpoints[current].label,
+                scores[current],
+                self.endpoints[primary].label,
+                scores[primary]
+            );
+            self.primary.store(primary, Ordering::Relaxed);
+        }
+        if scores.iter().all(|score| *score == 0.0) {
+            error!("No healthy RPC endpoint");
+        }
+    }
+
+    fn healths(&self) -> Vec<EndpointHealth> {
+        self.endpoints
+            .iter()
+            .map(|e| *e.health.lock().unwrap_or_else(|e| e.into_inner()))
+            .collect()
+    }
+
+    fn scores(&self) -> Vec<f64> {
+        let healths = self.healths();
+        let best_slot = healths.iter().filter_map(|h| h.slot).max().unwrap_or(0);
+        healths
+            .iter()
+            .map(|h| h.score(best_slot, self.max_slot_lag))
+            .collect()
+    }
+
+    /// Scores, slot lag, latency and error rate of every endpoint, and which one is primary, in
+    /// the Prometheus text format.
+    pub fn metrics(&self) -> String {
+        let healths = self.healths();
+        let scores = self.scores();
+        let best_slot = healths.iter().filter_map(|h| h.slot).max().unwrap_or(0);
+        let primary = self.primary.load(Ordering::Relaxed);
+
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, value: &dyn Fn(usize) -> String| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            for (i, endpoint) in self.endpoints.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "{}{{endpoint=\"{}\",host=\"{}\"}} {}",
+                    name,
+                    i,
+                    endpoint.label,
+                    value(i)
+                );
+            }
+        };
+        gauge("rpc_endpoint_score", "Health score from 0 to 100", &|i| {
+            format!("{:.1}", scores[i])
+        });
+        gauge(
+            "rpc_endpoint_primary",
+            "1 for the endpoint reads go to",
+            &|i| u8::from(i == primary).to_string(),
+        );
+        gauge(
+            "rpc_endpoint_slot_lag",
+            "Slots behind the most advanced endpoint",
+            &|i| {
+                healths[i]
+                    .slot
+                    .map_or("NaN".to_string(), |slot| (best_slot - slot).to_string())
+            },
+        );
+        gauge(
+            "rpc_endpoint_latency_seconds",
+            "Average health check latency",
+            &|i| format!("{:.3}", healths[i].latency_ms / 1000.0),
+        );
+        gauge(
+            "rpc_endpoint_error_rate",
+            "Average share of failed health checks",
+            &|i| format!("{:.3}", healths[i].error_rate),
+        );
+        out
+    }
+
+    /// Replaces the metrics file, if one is configured.
+    pub fn write_metrics(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.metrics_path else {
+            return Ok(());
+        };
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.metrics())?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn log_scores(&self) {
+        let scores = self.scores();
+        for (endpoint, score) in self.endpoints.iter().zip(scores) {
+            info!("RPC {} score {:.1}", endpoint.label, score);
+        }
+    }
+
+    /// Notes the outcome of a read served by the primary.
+    fn track<T>(&self, result: anyhow::Result<T>) -> anyhow::Result<T> {
+        if result.is_err() {
+            let endpoint = &self.endpoints[self.primary.load(Ordering::Relaxed)];
+            endpoint
+                .health
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .record_failure();
+        }
+        result
+    }
+}
+
+impl AccountSource for RpcPool {
+    fn get_account(&self, pubkey: &Pubkey) -> anyhow::Result<Account> {
+        self.track(AccountSource::get_account(self.primary(), pubkey))
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        self.track(AccountSource::get_multiple_accounts(
+            self.primary(),
+            pubkeys,
+        ))
+    }
+}
+
+impl TxSender for RpcPool {
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        max_retries: u64,
+    ) -> Result<Signature, SendError> {
+        TxSender::send_transaction(self.primary(), tx, max_retries)
+    }
+}
+
+/// The host of `url`, e.g. `api.mainnet-beta.solana.com` for
+/// `https://api.mainnet-beta.solana.com/?api-key=...`.
+pub fn endpoint_label(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', '?']).next().unwrap_or(rest).to_string()
+}
+
+pub async fn health_check_loop(rpc_pool: Arc<RpcPool>, mut shutdown: Shutdown) {
+    while !shutdown.is_triggered() {
+        let pool = rpc_pool.clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || pool.check()).await {
+            error!("RPC health check panicked: {:?}", e);
+        }
+        if let Err(e) = rpc_pool.write_metrics() {
+            error!("Failed to write RPC metrics: {:?}", e);
+        }
+        shutdown.sleep(rpc_pool.health_check_interval).await;
+    }
+}
@@ -0,0 +1,161 @@
+use crate::blockhash::BlockhashService;
+use crate::config::SpamConfig;
+use crate::rpc::{SendError, TxSender};
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// How long an endpoint that is unhealthy or can't be reached is left out.
+const DISABLE_DURATION: Duration = Duration::from_secs(10);
+
+/// Allows `rate` sends per second on average and up to `burst` at once.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, burst: u32, now: Instant) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            rate,
+            burst,
+            tokens: burst,
+            updated_at: now,
+        }
+    }
+
+    /// Takes a token if one is available at `now`.
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.updated_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EndpointState {
+    bucket: Option<TokenBucket>,
+    paused_until: Option<Instant>,
+    /// Pause after the next rate limit answer. Doubles with each one in a row.
+    backoff: Duration,
+}
+
+/// A sending endpoint with its own rate limit, reacting to what its errors say: backs off when
+/// rate limited, leaves the endpoint out for a while when it is unhealthy or unreachable, and
+/// has the blockhash refetched when the endpoint no longer knows it.
+pub struct SendingEndpoint {
+    label: String,
+    inner: Arc<dyn TxSender>,
+    blockhash_service: Arc<BlockhashService>,
+    state: Mutex<EndpointState>,
+}
+
+impl SendingEndpoint {
+    pub fn new(
+        label: String,
+        inner: Arc<dyn TxSender>,
+        blockhash_service: Arc<BlockhashService>,
+        config: Option<&SpamConfig>,
+    ) -> Self {
+        let bucket = config.and_then(|c| c.rate_limit).map(|rate| {
+            let burst = config
+                .and_then(|c| c.rate_limit_burst)
+                .unwrap_or(rate.ceil() as u32);
+            TokenBucket::new(rate, burst, Instant::now())
+        });
+        Self {
+            label,
+            inner,
+            blockhash_service,
+            state: Mutex::new(EndpointState {
+                bucket,
+                paused_until: None,
+                backoff: INITIAL_BACKOFF,
+            }),
+        }
+    }
+
+    /// Fails with `Paused` or `OverLimit` when the endpoint shouldn't be sent to right now.
+    fn admit(&self) -> Result<(), SendError> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(until) = state.paused_until {
+            if now < until {
+                return Err(SendError::Paused(until - now));
+            }
+            state.paused_until = None;
+        }
+        let within_limit = state
+            .bucket
+            .as_mut()
+            .is_none_or(|bucket| bucket.try_take(now));
+        if within_limit {
+            Ok(())
+        } else {
+            Err(SendError::OverLimit)
+        }
+    }
+
+    fn react(&self, tx: &VersionedTransaction, error: &SendError) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match error {
+            SendError::RateLimited => {
+                warn!(
+                    "RPC {} rate limited sending, backing off for {}ms",
+                    self.label,
+                    state.backoff.as_millis()
+                );
+                state.paused_until = Some(Instant::now() + state.backoff);
+                state.backoff = (state.backoff * 2).min(MAX_BACKOFF);
+            }
+            SendError::NodeUnhealthy(_) | SendError::Connection(_) => {
+                warn!(
+                    "Leaving out RPC {} for {}s: {}",
+                    self.label,
+                    DISABLE_DURATION.as_secs(),
+                    error
+                );
+                state.paused_until = Some(Instant::now() + DISABLE_DURATION);
+            }
+            SendError::BlockhashNotFound => {
+                self.blockhash_service
+                    .invalidate(tx.message.recent_blockhash());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl TxSender for SendingEndpoint {
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        max_retries: u64,
+    ) -> Result<Signature, SendError> {
+        self.admit()?;
+        match self.inner.send_transaction(tx, max_retries) {
+            Ok(signature) => {
+                self.state.lock().unwrap_or_else(|e| e.into_inner()).backoff = INITIAL_BACKOFF;
+                Ok(signature)
+            }
+            Err(e) => {
+                self.react(tx, &e);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// The state of one account at a slot. Snapshot files hold one JSON object per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub slot: u64,
+    /// Unix time in milliseconds when the state was read.
+    pub timestamp: u64,
+    pub address: String,
+    pub owner: String,
+    /// Account data, base64 encoded.
+    pub data: String,
+}
+
+impl AccountSnapshot {
+    pub fn new(slot: u64, address: &Pubkey, account: &Account) -> Self {
+        Self {
+            slot,
+            timestamp: unix_millis(),
+            address: address.to_string(),
+            owner: account.owner.to_string(),
+            data: STANDARD.encode(&account.data),
+        }
+    }
+
+    pub fn pubkey(&self) -> anyhow::Result<Pubkey> {
+        Pubkey::from_str(&self.address)
+            .map_err(|e| anyhow::anyhow!("Invalid snapshot address {}: {}", self.address, e))
+    }
+
+    pub fn account(&self) -> anyhow::Result<Account> {
+        Ok(Account {
+            lamports: 0,
+            data: STANDARD.decode(&self.data)?,
+            owner: Pubkey::from_str(&self.owner)
+                .map_err(|e| anyhow::anyhow!("Invalid snapshot owner {}: {}", self.owner, e))?,
+            executable: false,
+            rent_epoch: 0,
+        })
+    }
+}
+
+/// Writes snapshots to gzip compressed JSONL files in a directory, starting a new file
+/// once the current one reaches `max_bytes` or `max_age`.
+pub struct SnapshotWriter {
+    dir: PathBuf,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    current: Option<SnapshotFile>,
+}
+
+struct SnapshotFile {
+    path: PathBuf,
+    encoder: GzEncoder<File>,
+    opened_at: Instant,
+}
+
+impl SnapshotWriter {
+    pub fn new(
+        dir: &Path,
+        max_bytes: Option<u64>,
+        max_age: Option<Duration>,
+    ) -> anyhow::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            max_age,
+            current: None,
+        })
+    }
+
+    pub fn write(&mut self, snapshot: &AccountSnapshot) -> anyhow::Result<()> {
+        if self.current.is_none() {
+            let path = self
+                .dir
+                .join(format!("snapshots-{}.jsonl.gz", unix_millis()));
+            info!("Recording to {}", path.display());
+            self.current = Some(SnapshotFile {
+                encoder: GzEncoder::new(File::create(&path)?, Compression::default()),
+                path,
+                opened_at: Instant::now(),
+            });
+        }
+        let file = self.current.as_mut().unwrap();
+        serde_json::to_writer(&mut file.encoder, snapshot)?;
+        file.encoder.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Makes everything written so far readable, and closes the file once it is full or old enough.
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        let Some(file) = self.current.as_mut() else {
+            return Ok(());
+        };
+        file.encoder.flush()?;
+        let size = file.encoder.get_ref().metadata()?.len();
+        let full = self.max_bytes.is_some_and(|max| size >= max);
+        let expired = self
+            .max_age
+            .is_some_and(|max| file.opened_at.elapsed() >= max);
+        if full || expired {
+            self.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Closes the current file. The next write starts a new one.
+    pub fn finish(&mut self) -> anyhow::Result<()> {
+        if let Some(file) = self.current.take() {
+            file.encoder.finish()?;
+            info!("Closed {}", file.path.display());
+        }
+        Ok(())
+    }
+}
+
+/// Reads a snapshot file, or every `.jsonl` and `.jsonl.gz` file of a directory, in slot order.
+pub fn read_snapshots(path: &Path) -> anyhow::Result<Vec<AccountSnapshot>> {
+    let mut snapshots = Vec::new();
+    for file in snapshot_files(path)? {
+        read_snapshot_file(&file, &mut snapshots)?;
+    }
+    // Stable, so states of one account at the same slot keep their file order
+    snapshots.sort_by_key(|s: &AccountSnapshot| s.slot);
+    Ok(snapshots)
+}
+
+fn read_snapshot_file(file: &Path, snapshots: &mut Vec<AccountSnapshot>) -> anyhow::Result<()> {
+    let reader: Box<dyn BufRead> = if is_gzip(file) {
+        Box::new(BufReader::new(MultiGzDecoder::new(File::open(file)?)))
+    } else {
+        Box::new(BufReader::new(File::open(file)?))
+    };
+    for (i, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            // A recorder that was killed leaves its last file without the gzip trailer
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                warn!(
+                    "{} ends after line {}, skipping the rest",
+                    file.display(),
+                    i
+                );
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let snapshot = serde_json::from_str(&line)
+            .map_err(|e| anyhow::anyhow!("{}:{}: {}", file.display(), i + 1, e))?;
+        snapshots.push(snapshot);
+    }
+    Ok(())
+}
+
+fn is_gzip(file: &Path) -> bool {
+    file.extension().is_some_and(|ext| ext == "gz")
+}
+
+fn snapshot_files(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let file = entry?.path();
+        let name = file.to_string_lossy();
+        if name.ends_with(".jsonl") || name.ends_with(".jsonl.gz") {
+            files.push(file);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
@@ -0,0 +1,254 @@
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{mpsc, watch};
+use tokio::task::{Id, JoinError, JoinSet};
+use tracing::{error, info, warn};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A task that ran at least this long before exiting starts over with the initial backoff.
+const STABLE_RUN: Duration = Duration::from_secs(60);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub type TaskFactory = Arc<dyn Fn(Shutdown) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Cloneable handle that tells supervised tasks to stop.
+#[derive(Clone)]
+pub struct Shutdown {
+    receiver: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    pub fn is_triggered(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Completes once shutdown has been triggered.
+    pub async fn wait(&mut self) {
+        while !*self.receiver.borrow_and_update() {
+            if self.receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Sleeps for `duration`, returning `false` early if shutdown is triggered.
+    pub async fn sleep(&mut self, duration: Duration) -> bool {
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => true,
+            _ = self.wait() => false,
+        }
+    }
+}
+
+enum Command {
+    Spawn { name: String, factory: TaskFactory },
+    Stop { name: String },
+}
+
+/// Lets running tasks start and stop other supervised tasks.
+#[derive(Clone)]
+pub struct SupervisorHandle {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl SupervisorHandle {
+    pub fn spawn<F>(&self, name: impl Into<String>, factory: F)
+    where
+        F: Fn(Shutdown) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    {
+        let _ = self.commands.send(Command::Spawn {
+            name: name.into(),
+            factory: Arc::new(factory),
+        });
+    }
+
+    /// Asks the named task to stop after its current iteration. It is not restarted.
+    pub fn stop(&self, name: impl Into<String>) {
+        let _ = self.commands.send(Command::Stop { name: name.into() });
+    }
+}
+
+struct SupervisedTask {
+    name: String,
+    factory: TaskFactory,
+    restarts: u32,
+    started_at: Instant,
+    stop_tx: watch::Sender<bool>,
+}
+
+/// Owns the long running tasks of the bot, restarts the ones that exit or
+/// panic, and stops all of them on SIGINT/SIGTERM.
+pub struct Supervisor {
+    tasks: JoinSet<()>,
+    registry: HashMap<Id, SupervisedTask>,
+    commands_tx: mpsc::UnboundedSender<Command>,
+    commands_rx: mpsc::UnboundedReceiver<Command>,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        let (commands_tx, commands_rx) = mpsc::unbounded_channel();
+        Self {
+            tasks: JoinSet::new(),
+            registry: HashMap::new(),
+            commands_tx,
+            commands_rx,
+        }
+    }
+
+    pub fn handle(&self) -> SupervisorHandle {
+        SupervisorHandle {
+            commands: self.commands_tx.clone(),
+        }
+    }
+
+    pub fn spawn<F>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn(Shutdown) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    {
+        self.spawn_factory(name.into(), Arc::new(factory));
+    }
+
+    fn spawn_factory(&mut self, name: String, factory: TaskFactory) {
+        let (stop_tx, _) = watch::channel(false);
+        let task = SupervisedTask {
+            name,
+            factory,
+            restarts: 0,
+            started_at: Instant::now(),
+            stop_tx,
+        };
+        self.start(task, Duration::ZERO);
+    }
+
+    fn start(&mut self, mut task: SupervisedTask, delay: Duration) {
+        let shutdown = Shutdown {
+            receiver: task.stop_tx.subscribe(),
+        };
+        let future = (task.factory)(shutdown);
+        task.started_at = Instant::now() + delay;
+        let handle = self.tasks.spawn(async move {
+            tokio::time::sleep(delay).await;
+            future.await;
+        });
+        self.registry.insert(handle.id(), task);
+    }
+
+    /// Supervises tasks until SIGINT or SIGTERM, then signals shutdown and
+    /// waits for tasks to finish their current iteration.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        let mut sigterm = signal(SignalKind::terminate())?;
+
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {
+                    info!("SIGINT received, shutting down");
+                    break;
+                }
+                _ = sigterm.recv() => {
+                    info!("SIGTERM received, shutting down");
+                    break;
+                }
+                Some(result) = self.tasks.join_next_with_id() => {
+                    self.handle_exit(result);
+                }
+                Some(command) = self.commands_rx.recv() => {
+                    self.handle_command(command);
+                }
+            }
+        }
+
+        for task in self.registry.values() {
+            task.stop_tx.send_replace(true);
+        }
+
+        let drain = async {
+            while let Some(result) = self.tasks.join_next_with_id().await {
+                if let Err(e) = result {
+                    if e.is_panic() {
+                        error!("Task panicked during shutdown: {}", e);
+                    }
+                }
+            }
+        };
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, drain).await.is_err() {
+            warn!(
+                "Tasks did not stop within {:?}, aborting them",
+                SHUTDOWN_TIMEOUT
+            );
+            self.tasks.shutdown().await;
+        }
+        info!("All tasks stopped");
+
+        Ok(())
+    }
+
+    fn handle_command(&mut self, command: Command) {
+        match command {
+            Command::Spawn { name, factory } => {
+                info!("Starting task {}", name);
+                self.spawn_factory(name, factory);
+            }
+            Command::Stop { name } => {
+                let mut found = false;
+                for task in self.registry.values().filter(|t| t.name == name) {
+                    found |= !*task.stop_tx.borrow();
+                    task.stop_tx.send_replace(true);
+                }
+                if found {
+                    info!("Stopping task {}", name);
+                } else {
+                    warn!("No running task named {}", name);
+                }
+            }
+        }
+    }
+
+    fn handle_exit(&mut self, result: Result<(Id, ()), JoinError>) {
+        let id = match &result {
+            Ok((id, _)) => *id,
+            Err(e) => e.id(),
+        };
+        let Some(mut task) = self.registry.remove(&id) else {
+            return;
+        };
+
+        if *task.stop_tx.borrow() {
+            info!("Task {} stopped", task.name);
+            return;
+        }
+
+        match result {
+            Ok(_) => warn!("Task {} exited", task.name),
+            Err(e) if e.is_panic() => error!("Task {} panicked: {}", task.name, e),
+            Err(_) => {
+                info!("Task {} was cancelled", task.name);
+                return;
+            }
+        }
+
+        if task.started_at.elapsed() >= STABLE_RUN {
+            task.restarts = 0;
+        }
+        let delay = INITIAL_BACKOFF
+            .saturating_mul(2u32.saturating_pow(task.restarts))
+            .min(MAX_BACKOFF);
+        task.restarts += 1;
+
+        info!(
+            "Restarting task {} in {:?} (restart #{})",
+            task.name, delay, task.restarts
+        );
+        self.start(task, delay);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::config::TpuConfig;
+use crate::rpc::{SendError, TxSender};
+use crate::rpc_pool::RpcPool;
+use crate::supervisor::Shutdown;
+use solana_client::connection_cache::ConnectionCache;
+use solana_connection_cache::client_connection::ClientConnection;
+use solana_sdk::clock::NUM_CONSECUTIVE_LEADER_SLOTS;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+const DEFAULT_LEADER_COUNT: usize = 2;
+/// Slots of the leader schedule fetched at once.
+const SCHEDULE_WINDOW: u64 = 256;
+const SLOT_POLL_INTERVAL: Duration = Duration::from_millis(400);
+const NODE_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+const CONNECTION_POOL_SIZE: usize = 4;
+/// QUIC port of nodes that only advertise their UDP TPU port.
+const QUIC_PORT_OFFSET: u16 = 6;
+
+/// The first `count` distinct leaders from `current_slot` on, given the leaders of the slots
+/// starting at `first_slot`.
+pub fn upcoming_leaders(
+    schedule: &[Pubkey],
+    first_slot: u64,
+    current_slot: u64,
+    count: usize,
+) -> Vec<Pubkey> {
+    let start = current_slot.saturating_sub(first_slot) as usize;
+    let mut leaders: Vec<Pubkey> = Vec::new();
+    for leader in schedule.iter().skip(start) {
+        if leaders.len() == count {
+            break;
+        }
+        if !leaders.contains(leader) {
+            leaders.push(*leader);
+        }
+    }
+    leaders
+}
+
+/// TPU QUIC address of a node, derived from its UDP TPU port when it doesn't advertise one.
+/// `None` when the derived port would be past the last port.
+pub fn tpu_quic_address(
+    tpu_quic: Option<SocketAddr>,
+    tpu: Option<SocketAddr>,
+) -> Option<SocketAddr> {
+    tpu_quic.or_else(|| {
+        let tpu = tpu?;
+        let port = tpu.port().checked_add(QUIC_PORT_OFFSET)?;
+        Some(SocketAddr::new(tpu.ip(), port))
+    })
+}
+
+#[derive(Default)]
+struct LeaderState {
+    slot: u64,
+    /// Leaders of the slots from `first_slot` on.
+    schedule: Vec<Pubkey>,
+    first_slot: u64,
+    tpu_quic: HashMap<Pubkey, SocketAddr>,
+    nodes_refreshed_at: Option<Instant>,
+}
+
+/// Follows the current slot, the leader schedule and the cluster node list to know where the
+/// next leaders accept transactions.
+pub struct LeaderTracker {
+    rpc_pool: Arc<RpcPool>,
+    leader_count: usize,
+    state: Mutex<LeaderState>,
+}
+
+impl LeaderTracker {
+    pub fn new(rpc_pool: Arc<RpcPool>, config: &TpuConfig) -> Self {
+        Self {
+            rpc_pool,
+            leader_count: config.leader_count.unwrap_or(DEFAULT_LEADER_COUNT),
+            state: Mutex::new(LeaderState::default()),
+        }
+    }
+
+    /// Reads the current slot, and the schedule and node list when they run out or get old.
+    pub fn refresh(&self) -> anyhow::Result<()> {
+        let slot = self
+            .rpc_pool
+            .primary()
+            .get_slot_with_commitment(CommitmentConfig::processed())?;
+        let (needs_schedule, needs_nodes) = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.slot = slot;
+            let lookahead = (self.leader_count as u64 + 1) * NUM_CONSECUTIVE_LEADER_SLOTS;
+            (
+                slot < state.first_slot
+                    || slot + lookahead >= state.first_slot + state.schedule.len() as u64,
+                state
+                    .nodes_refreshed_at
+                    .is_none_or(|at| at.elapsed() >= NODE_REFRESH_INTERVAL),
+            )
+        };
+
+        if needs_schedule {
+            let schedule = self
+                .rpc_pool
+                .primary()
+                .get_slot_leaders(slot, SCHEDULE_WINDOW)?;
+            debug!("Fetched {} slot leaders from slot {}", schedule.len(), slot);
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.schedule = schedule;
+            state.first_slot = slot;
+        }
+
+        if needs_nodes {
+            let mut tpu_quic = HashMap::new();
+            for node in self.rpc_pool.primary().get_cluster_nodes()? {
+                let address = tpu_quic_address(node.tpu_quic, node.tpu);
+                if let (Ok(pubkey), Some(address)) = (Pubkey::from_str(&node.pubkey), address) {
+                    tpu_quic.insert(pubkey, address);
+                }
+            }
+            info!("Found TPU addresses of {} cluster nodes", tpu_quic.len());
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.tpu_quic = tpu_quic;
+            state.nodes_refreshed_at = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    /// TPU QUIC addresses of the next leaders. Leaders without a known address are skipped.
+    pub fn leader_addresses(&self) -> Vec<SocketAddr> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        upcoming_leaders(
+            &state.schedule,
+            state.first_slot,
+            state.slot,
+            self.leader_count,
+        )
+        .iter()
+        .filter_map(|leader| state.tpu_quic.get(leader).copied())
+        .collect()
+    }
+}
+
+pub async fn leader_tracker_loop(tracker: Arc<LeaderTracker>, mut shutdown: Shutdown) {
+    while !shutdown.is_triggered() {
+        if let Err(e) = tracker.refresh() {
+            error!("Failed to refresh the leader schedule: {:?}", e);
+        }
+        shutdown.sleep(SLOT_POLL_INTERVAL).await;
+    }
+}
+
+/// Sends transactions straight to the TPU of the next leaders over QUIC.
+pub struct TpuSender {
+    tracker: Arc<LeaderTracker>,
+    connection_cache: ConnectionCache,
+}
+
+impl TpuSender {
+    pub fn new(tracker: Arc<LeaderTracker>) -> Self {
+        Self {
+            tracker,
+            connection_cache: ConnectionCache::new_quic("arbitrage-tpu", CONNECTION_POOL_SIZE),
+        }
+    }
+}
+
+impl TxSender for TpuSender {
+    /// Queues the transaction for every leader and returns without waiting for delivery, so
+    /// `max_retries` does not apply. Fails when it could not be queued for any leader.
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        _max_retries: u64,
+    ) -> Result<Signature, SendError> {
+        let addresses = self.tracker.leader_addresses();
+        if addresses.is_empty() {
+            return Err(SendError::Other(
+                "No TPU address known for the upcoming leaders".to_string(),
+            ));
+        }
+        let wire = bincode::serialize(tx).map_err(|e| SendError::Other(e.to_string()))?;
+        let mut queued = 0;
+        let mut last_error = None;
+        for address in &addresses {
+            match self
+                .connection_cache
+                .get_connection(address)
+                .send_data_async(wire.clone())
+            {
+                Ok(()) => queued += 1,
+                Err(e) => {
+                    warn!("Failed to queue transaction for TPU {}: {}", address, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        if let (0, Some(e)) = (queued, last_error) {
+            return Err(SendError::Connection(format!(
+                "no leader TPU accepted the transaction, last error: {}",
+                e
+            )));
+        }
+        debug!(
+            "Transaction queued for {} of {} leaders",
+            queued,
+            addresses.len()
+        );
+        Ok(tx.signatures[0])
+    }
+}
@@ -1,20 +1,27 @@
+use crate::base_assets::{BaseAssets, FlashloanVault};
 use crate::config::Config;
+use crate::dex::heaven::constants::{
+    heaven_program_id, heaven_protocol_account_1, heaven_protocol_account_2,
+};
 use crate::dex::raydium::{raydium_authority, raydium_cp_authority};
-use crate::dex::heaven::constants::{heaven_program_id, heaven_protocol_account_1, heaven_protocol_account_2};
 use crate::dex::solfi::constants::solfi_program_id;
 use crate::dex::vertigo::constants::vertigo_program_id;
+use crate::nonce::NoncePool;
 use crate::pools::MintPoolData;
-use solana_client::rpc_client::RpcClient;
+use crate::rpc::TxSender;
+use crate::wallets::WalletSigner;
 use solana_program::instruction::Instruction;
 use solana_sdk::address_lookup_table::AddressLookupTableAccount;
-use solana_sdk::commitment_config::CommitmentLevel;
 use solana_sdk::compute_budget::ComputeBudgetInstruction;
 use solana_sdk::hash::Hash;
 use solana_sdk::message::v0::Message;
-use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::message::{CompileError, VersionedMessage};
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::signature::Signature;
 use solana_sdk::signer::Signer;
 use solana_sdk::transaction::VersionedTransaction;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use tracing::{debug, error, info};
 
 use crate::constants::sol_mint;
@@ -34,14 +41,42 @@ use spl_associated_token_account::ID as associated_token_program_id;
 use spl_token::ID as token_program_id;
 use std::str::FromStr;
 
+/// Accounts a transaction may lock. Raised to 128 on clusters where the larger limit is active.
+const DEFAULT_MAX_ACCOUNT_LOCKS: usize = 64;
+
+/// What the sent transactions are valid against.
+#[derive(Clone, Copy)]
+pub enum TxLifetime<'a> {
+    /// A recent blockhash, valid for about 150 slots.
+    Blockhash(Hash),
+    /// Each transaction takes its own durable nonce from the pool and advances it first.
+    Nonce(&'a NoncePool),
+}
+
+/// A transaction accepted by a sender.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SentTransaction {
+    pub signature: Signature,
+    /// The nonce account the transaction advances and the nonce it was signed with, in durable
+    /// nonce mode.
+    pub nonce: Option<(Pubkey, Hash)>,
+}
+
+/// Builds the arbitrage transactions of `mint_pool_data`, trading from `wallet_kp` with fees
+/// paid by `fee_payer`, and sends each through every sender.
+#[allow(clippy::too_many_arguments)]
 pub async fn build_and_send_transaction(
-    wallet_kp: &Keypair,
+    wallet_kp: &WalletSigner,
+    fee_payer: &WalletSigner,
     config: &Config,
+    base_assets: &BaseAssets,
     mint_pool_data: &MintPoolData,
-    rpc_clients: &[Arc<RpcClient>],
-    blockhash: Hash,
+    pool_selections: &PoolSelections,
+    minimum_profit: u64,
+    senders: &[Arc<dyn TxSender>],
+    lifetime: TxLifetime<'_>,
     address_lookup_table_accounts: &[AddressLookupTableAccount],
-) -> anyhow::Result<Vec<Signature>> {
+) -> anyhow::Result<Vec<SentTransaction>> {
     let enable_flashloan = config.flashloan.as_ref().map_or(false, |k| k.enabled);
     let compute_unit_limit = config.bot.compute_unit_limit;
     let mut instructions = vec![];
@@ -56,29 +91,126 @@ pub async fn build_and_send_transaction(
         ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price);
     instructions.push(compute_budget_price_ix);
 
-    let swap_ix = create_swap_instruction(
-        wallet_kp,
-        mint_pool_data,
-        compute_unit_limit,
-        enable_flashloan,
-    )?;
-
-    let mut all_instructions = instructions.clone();
+    let max_account_locks = config
+        .bot
+        .max_account_locks
+        .unwrap_or(DEFAULT_MAX_ACCOUNT_LOCKS);
+    let compile = |pool_data: &MintPoolData,
+                   advance_nonce_ix: Option<&Instruction>,
+                   blockhash: Hash|
+     -> anyhow::Result<VersionedTransaction> {
+        let swap_ix = create_swap_instruction(
+            wallet_kp,
+            pool_data,
+            base_assets,
+            compute_unit_limit,
+            enable_flashloan,
+            minimum_profit,
+        )?;
+
+        // The nonce advance has to be the first instruction
+        let mut all_instructions: Vec<Instruction> =
+            advance_nonce_ix.into_iter().cloned().collect();
+        all_instructions.extend(instructions.iter().cloned());
+
+        debug!("Adding swap instruction");
+        all_instructions.push(swap_ix);
+
+        let message = Message::try_compile(
+            &fee_payer.pubkey(),
+            &all_instructions,
+            address_lookup_table_accounts,
+            blockhash,
+        )?;
+
+        // Placeholder signatures take the same space as real ones. Only the transaction that
+        // is sent gets signed, as a remote signer makes each signature a network round trip.
+        let tx = VersionedTransaction {
+            signatures: vec![
+                Signature::default();
+                usize::from(message.header.num_required_signatures)
+            ],
+            message: VersionedMessage::V0(message),
+        };
+        check_transaction_limits(&tx, max_account_locks)?;
+        Ok(tx)
+    };
+    let sign = |tx: VersionedTransaction| -> anyhow::Result<VersionedTransaction> {
+        let signers: Vec<&dyn Signer> = if fee_payer.pubkey() == wallet_kp.pubkey() {
+            vec![wallet_kp]
+        } else {
+            vec![fee_payer, wallet_kp]
+        };
+        Ok(VersionedTransaction::try_new(tx.message, &signers)?)
+    };
 
-    debug!("Adding swap instruction");
-    all_instructions.push(swap_ix);
+    let mint_pool_data = &executable_pools(mint_pool_data, base_assets);
+    if mint_pool_data.pools().len() < 2 {
+        anyhow::bail!(
+            "Mint {} has fewer than two pools with a base the executor can trade",
+            mint_pool_data.mint
+        );
+    }
 
-    let message = Message::try_compile(
-        &wallet_kp.pubkey(),
-        &all_instructions,
-        address_lookup_table_accounts,
-        blockhash,
-    )?;
+    let groups = split_by_base(mint_pool_data, enable_flashloan);
+    if groups.len() > 1 {
+        debug!(
+            "Pools of mint {} use {} bases, sending one flashloan transaction per base",
+            mint_pool_data.mint,
+            groups.len()
+        );
+    }
 
-    let tx = VersionedTransaction::try_new(
-        solana_sdk::message::VersionedMessage::V0(message),
-        &[wallet_kp],
-    )?;
+    let mut txs = Vec::new();
+    // Nonces taken for transactions that end up not being sent are handed back
+    let mut acquired = Vec::new();
+    let release = |acquired: &[Pubkey]| {
+        if let TxLifetime::Nonce(pool) = lifetime {
+            acquired.iter().for_each(|address| pool.release(address));
+        }
+    };
+    for group in groups.iter().filter(|g| g.pools().len() >= 2) {
+        let (advance_nonce_ix, blockhash) = match lifetime {
+            TxLifetime::Blockhash(blockhash) => (None, blockhash),
+            TxLifetime::Nonce(pool) => {
+                let Some((address, nonce)) = pool.acquire() else {
+                    release(&acquired);
+                    anyhow::bail!("Every nonce account is in use by a recent transaction");
+                };
+                acquired.push(address);
+                (Some(pool.advance_instruction(&address)), nonce)
+            }
+        };
+        let compile_group =
+            |pool_data: &MintPoolData| compile(pool_data, advance_nonce_ix.as_ref(), blockhash);
+        let tx = match pool_selections.get(group) {
+            Some(selected) => compile_group(&group.with_pools(&selected)),
+            None => compile_group(group),
+        };
+        let tx = match tx {
+            Err(e) if is_limit_error(&e) => {
+                debug!("Transaction does not fit: {}", e);
+                select_fitting_pools(group, compile_group).map(|(selected, tx)| {
+                    pool_selections.insert(group, selected);
+                    tx
+                })
+            }
+            tx => tx,
+        };
+        match tx.and_then(sign) {
+            Ok(tx) => txs.push(tx),
+            Err(e) => {
+                release(&acquired);
+                return Err(e);
+            }
+        }
+    }
+    if txs.is_empty() {
+        anyhow::bail!(
+            "Mint {} mixes base assets and no base has two pools to flashloan through",
+            mint_pool_data.mint
+        );
+    }
 
     let max_retries = config
         .spam
@@ -86,43 +218,317 @@ pub async fn build_and_send_transaction(
         .and_then(|s| s.max_retries)
         .unwrap_or(3);
 
-    let mut signatures = Vec::new();
+    let mut sent = Vec::new();
+
+    for (index, tx) in txs.iter().enumerate() {
+        let nonce = acquired
+            .get(index)
+            .map(|address| (*address, *tx.message.recent_blockhash()));
+        let sent_before = sent.len();
+        for (i, sender) in senders.iter().enumerate() {
+            debug!("Sending transaction through RPC client {}", i);
+
+            let signature = match sender.send_transaction(tx, max_retries) {
+                Ok(sig) => sig,
+                Err(e) if e.is_skipped() => {
+                    debug!("Skipped RPC client {}: {}", i, e);
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to send transaction through RPC client {}: {}", i, e);
+                    continue;
+                }
+            };
+
+            info!(
+                "Transaction sent successfully through RPC client {}: {}",
+                i, signature
+            );
+            sent.push(SentTransaction { signature, nonce });
+        }
+        if sent.len() == sent_before {
+            release(acquired.get(index..=index).unwrap_or_default());
+        }
+    }
 
-    for (i, client) in rpc_clients.iter().enumerate() {
-        debug!("Sending transaction through RPC client {}", i);
+    Ok(sent)
+}
 
-        let signature = match send_transaction_with_retries(client, &tx, max_retries).await {
-            Ok(sig) => sig,
-            Err(e) => {
-                error!("Failed to send transaction through RPC client {}: {}", i, e);
-                continue;
+/// The pool sets that are sent as separate transactions. A flashloan borrows a
+/// single base, so with flashloan enabled the pools are split by base mint.
+pub fn split_by_base(mint_pool_data: &MintPoolData, use_flashloan: bool) -> Vec<MintPoolData> {
+    if !use_flashloan {
+        return vec![mint_pool_data.clone()];
+    }
+
+    let mut groups: Vec<(Pubkey, HashSet<Pubkey>)> = Vec::new();
+    for pool in mint_pool_data.pools() {
+        match groups.iter_mut().find(|(base, _)| *base == pool.base_mint) {
+            Some((_, pools)) => {
+                pools.insert(pool.pool);
             }
-        };
+            None => groups.push((pool.base_mint, HashSet::from([pool.pool]))),
+        }
+    }
+    groups
+        .iter()
+        .map(|(_, pools)| mint_pool_data.with_pools(pools))
+        .collect()
+}
+
+/// The pools of `mint_pool_data` whose base the executor can trade. The executor
+/// bridges through one base asset per transaction, so pools of SOL and of the first
+/// base asset the mint uses are kept. Other pools are only used for cycle detection.
+pub fn executable_pools(mint_pool_data: &MintPoolData, base_assets: &BaseAssets) -> MintPoolData {
+    let pools = mint_pool_data.pools();
+    let bridge = base_assets.bridged(pools.iter().map(|p| &p.base_mint));
+    let executable: HashSet<Pubkey> = pools
+        .iter()
+        .filter(|p| p.base_mint == sol_mint() || bridge.is_some_and(|b| b.mint == p.base_mint))
+        .map(|p| p.pool)
+        .collect();
+    mint_pool_data.with_pools(&executable)
+}
+
+/// Why a transaction does not fit.
+#[derive(Debug, thiserror::Error)]
+pub enum TxLimitError {
+    #[error("Transaction is {size} bytes, over the {limit} byte limit")]
+    TooLarge { size: usize, limit: usize },
+    #[error("Transaction locks {count} accounts, over the limit of {limit}")]
+    TooManyLocks { count: usize, limit: usize },
+}
+
+/// Whether `error` says the transaction has too many accounts or bytes, as opposed to
+/// anything leaving out pools would not fix.
+fn is_limit_error(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<TxLimitError>().is_some()
+        || matches!(
+            error.downcast_ref::<CompileError>(),
+            Some(
+                CompileError::AccountIndexOverflow | CompileError::AddressTableLookupIndexOverflow
+            )
+        )
+}
+
+/// Checks that a transaction can be serialized into one packet and stays within the account lock limit.
+pub fn check_transaction_limits(
+    tx: &VersionedTransaction,
+    max_account_locks: usize,
+) -> anyhow::Result<()> {
+    let size = bincode::serialized_size(tx)? as usize;
+    if size > PACKET_DATA_SIZE {
+        return Err(TxLimitError::TooLarge {
+            size,
+            limit: PACKET_DATA_SIZE,
+        }
+        .into());
+    }
 
-        info!(
-            "Transaction sent successfully through RPC client {}: {}",
-            i, signature
+    let VersionedMessage::V0(message) = &tx.message else {
+        return Ok(());
+    };
+    let account_count = message.account_keys.len()
+        + message
+            .address_table_lookups
+            .iter()
+            .map(|l| l.writable_indexes.len() + l.readonly_indexes.len())
+            .sum::<usize>();
+    if account_count > max_account_locks {
+        return Err(TxLimitError::TooManyLocks {
+            count: account_count,
+            limit: max_account_locks,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Pools picked for pool sets whose transaction did not fit, so the pick isn't redone every
+/// tick. A pick holds while the set has the same pools in the same liquidity order.
+#[derive(Default)]
+pub struct PoolSelections {
+    /// Keyed by the sorted pools of the set.
+    selections: Mutex<HashMap<Vec<Pubkey>, PoolSelection>>,
+}
+
+struct PoolSelection {
+    by_liquidity: Vec<Pubkey>,
+    selected: HashSet<Pubkey>,
+}
+
+impl PoolSelections {
+    /// The pools picked for `mint_pool_data`, unless its pools or their ranking changed since.
+    pub fn get(&self, mint_pool_data: &MintPoolData) -> Option<HashSet<Pubkey>> {
+        let by_liquidity = mint_pool_data.pools_by_liquidity();
+        let selections = self.selections.lock().unwrap_or_else(|e| e.into_inner());
+        selections
+            .get(&sorted(&by_liquidity))
+            .filter(|s| s.by_liquidity == by_liquidity)
+            .map(|s| s.selected.clone())
+    }
+
+    fn insert(&self, mint_pool_data: &MintPoolData, selected: HashSet<Pubkey>) {
+        let by_liquidity = mint_pool_data.pools_by_liquidity();
+        let mut selections = self.selections.lock().unwrap_or_else(|e| e.into_inner());
+        selections.insert(
+            sorted(&by_liquidity),
+            PoolSelection {
+                by_liquidity,
+                selected,
+            },
         );
-        signatures.push(signature);
     }
+}
 
-    Ok(signatures)
+fn sorted(pools: &[Pubkey]) -> Vec<Pubkey> {
+    let mut pools = pools.to_vec();
+    pools.sort();
+    pools
 }
 
-async fn send_transaction_with_retries(
-    client: &RpcClient,
-    tx: &VersionedTransaction,
-    max_retries: u64,
-) -> anyhow::Result<Signature> {
-    Ok(client.send_transaction_with_config(
-        tx,
-        solana_client::rpc_config::RpcSendTransactionConfig {
-            skip_preflight: true,
-            max_retries: Some(max_retries as usize),
-            preflight_commitment: Some(CommitmentLevel::Confirmed),
-            ..Default::default()
-        },
-    )?)
+/// Adds pools deepest liquidity first, skipping any that would push the transaction over a
+/// limit, and returns the pools that fit with their transaction.
+fn select_fitting_pools(
+    mint_pool_data: &MintPoolData,
+    compile: impl Fn(&MintPoolData) -> anyhow::Result<VersionedTransaction>,
+) -> anyhow::Result<(HashSet<Pubkey>, VersionedTransaction)> {
+    let mut selected = HashSet::new();
+    let mut best = None;
+
+    for pool in mint_pool_data.pools_by_liquidity() {
+        selected.insert(pool);
+        match compile(&mint_pool_data.with_pools(&selected)) {
+            Ok(tx) => best = Some(tx),
+            Err(e) if is_limit_error(&e) => {
+                debug!("Leaving out pool {}: {}", pool, e);
+                selected.remove(&pool);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    if selected.len() < 2 {
+        anyhow::bail!(
+            "Only {} pool(s) of mint {} fit in one transaction",
+            selected.len(),
+            mint_pool_data.mint
+        );
+    }
+    info!(
+        "Using {} of {} pools for mint {} to fit transaction limits",
+        selected.len(),
+        mint_pool_data.pools().len(),
+        mint_pool_data.mint
+    );
+    let tx = best.ok_or_else(|| anyhow::anyhow!("No pools fit in one transaction"))?;
+    Ok((selected, tx))
+}
+
+/// Fee collectors the executor accepts. One is picked at random per swap instruction.
+pub fn fee_collectors(use_flashloan: bool) -> Vec<Pubkey> {
+    if use_flashloan {
+        vec![Pubkey::from_str("6AGB9kqgSp2mQXwYpdrV4QVV8urvCaDS35U1wsLssy6H").unwrap()]
+    } else {
+        vec![
+            Pubkey::from_str("GPpkDpzCDmYJY5qNhYmM14c7rct1zmkjWc2CjR5g7RZ1").unwrap(),
+            Pubkey::from_str("J6c7noBHvWju4mMA3wXt3igbBSp2m9ATbA6cjMtAUged").unwrap(),
+            Pubkey::from_str("BjsfwxDu7GX7RRW6oSRTpMkASdXAgCcHnXEcatqSfuuY").unwrap(),
+        ]
+    }
+}
+
+/// The base a flashloan of `mint_pool_data` borrows: the base every pool shares, or SOL when
+/// that base can't be flashloaned and is reached through the bridge pool.
+fn flashloan_base_mint(
+    mint_pool_data: &MintPoolData,
+    base_assets: &BaseAssets,
+) -> anyhow::Result<Pubkey> {
+    let pools = mint_pool_data.pools();
+    let Some(first) = pools.first() else {
+        return Ok(sol_mint());
+    };
+    if let Some(other) = pools.iter().find(|p| p.base_mint != first.base_mint) {
+        anyhow::bail!(
+            "Flashloan needs one base per transaction but mint {} has pools on {} and {}",
+            mint_pool_data.mint,
+            first.base_mint,
+            other.base_mint
+        );
+    }
+    if base_assets.can_flashloan(&first.base_mint) {
+        Ok(first.base_mint)
+    } else {
+        Ok(sol_mint())
+    }
+}
+
+/// Vaults a flashloan of `base_mint` can borrow from, as (authority, token account). One is
+/// picked at random per swap instruction.
+pub fn flashloan_vaults(base_assets: &BaseAssets, base_mint: &Pubkey) -> Vec<(Pubkey, Pubkey)> {
+    let fixed_vault = base_assets
+        .get(base_mint)
+        .and_then(|a| a.flashloan_vault.as_ref());
+    if let Some(FlashloanVault::Fixed {
+        authority,
+        token_account,
+    }) = fixed_vault
+    {
+        return vec![(*authority, *token_account)];
+    }
+
+    let executor_program_id =
+        Pubkey::from_str("MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz").unwrap();
+    let pda_authority = Pubkey::from_str("5LFpzqgsxrSfhKwbaFiAEJ2kbc9QyimjKueswsyU4T3o").unwrap();
+    let ata_authority = Pubkey::from_str("4B2yxi8n7jr8w3K7cssokLNJZ6k2NjiwKwLdQ8L9dbAA").unwrap();
+    vec![
+        (
+            pda_authority,
+            derive_vault_token_account(&executor_program_id, base_mint).0,
+        ),
+        (
+            ata_authority,
+            spl_associated_token_account::get_associated_token_address(&ata_authority, base_mint),
+        ),
+    ]
+}
+
+/// Every account a swap instruction of `mint_pool_data` signed by `wallet_kp` can load from a
+/// lookup table. `create_swap_instruction` picks one fee collector and one flashloan vault per
+/// instruction; all of them are listed here.
+pub fn swap_lookup_accounts(
+    wallet_kp: &WalletSigner,
+    mint_pool_data: &MintPoolData,
+    base_assets: &BaseAssets,
+    compute_unit_limit: u32,
+    use_flashloan: bool,
+) -> anyhow::Result<Vec<Pubkey>> {
+    let swap_ix = create_swap_instruction(
+        wallet_kp,
+        mint_pool_data,
+        base_assets,
+        compute_unit_limit,
+        use_flashloan,
+        0,
+    )?;
+    let mut accounts: Vec<Pubkey> = swap_ix
+        .accounts
+        .iter()
+        .filter(|meta| !meta.is_signer)
+        .map(|meta| meta.pubkey)
+        .collect();
+    accounts.extend(fee_collectors(use_flashloan));
+    if use_flashloan {
+        let base_mint = flashloan_base_mint(mint_pool_data, base_assets)?;
+        for (authority, token_account) in flashloan_vaults(base_assets, &base_mint) {
+            accounts.extend([authority, token_account]);
+        }
+    }
+
+    accounts.retain(|pubkey| *pubkey != swap_ix.program_id);
+    accounts.sort();
+    accounts.dedup();
+    Ok(accounts)
 }
 
 /// Helper function to derive the vault token account PDA address for a given mint
@@ -131,27 +537,21 @@ pub fn derive_vault_token_account(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey
 }
 
 // See https://docs.solanamevbot.com/home/onchain-bot/onchain-program for more information
-fn create_swap_instruction(
-    wallet_kp: &Keypair,
+pub fn create_swap_instruction(
+    wallet_kp: &WalletSigner,
     mint_pool_data: &MintPoolData,
+    base_assets: &BaseAssets,
     compute_unit_limit: u32,
     use_flashloan: bool,
+    minimum_profit: u64,
 ) -> anyhow::Result<Instruction> {
     debug!("Creating swap instruction for all DEX types");
 
     let executor_program_id =
         Pubkey::from_str("MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz").unwrap();
 
-    let fee_collector = if use_flashloan {
-        Pubkey::from_str("6AGB9kqgSp2mQXwYpdrV4QVV8urvCaDS35U1wsLssy6H").unwrap()
-    } else {
-        let fee_accounts = [
-            Pubkey::from_str("GPpkDpzCDmYJY5qNhYmM14c7rct1zmkjWc2CjR5g7RZ1").unwrap(),
-            Pubkey::from_str("J6c7noBHvWju4mMA3wXt3igbBSp2m9ATbA6cjMtAUged").unwrap(),
-            Pubkey::from_str("BjsfwxDu7GX7RRW6oSRTpMkASdXAgCcHnXEcatqSfuuY").unwrap(),
-        ];
-        fee_accounts[rand::random::<usize>() % fee_accounts.len()]
-    };
+    let fee_accounts = fee_collectors(use_flashloan);
+    let fee_collector = fee_accounts[rand::random::<usize>() % fee_accounts.len()];
 
     let pump_global_config =
         Pubkey::from_str("ADyA8hdefvWN2dbGGWFotbzWxrAvLW83WG6QCVXvJKqw").unwrap();
@@ -162,9 +562,9 @@ fn create_swap_instruction(
 
     let wallet = wallet_kp.pubkey();
     let sol_mint_pubkey = sol_mint();
-    let wallet_sol_account = mint_pool_data.wallet_wsol_account;
-    let usdc_mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
-    let usd1_mint = Pubkey::from_str("USD1ttGY1N17NEEHLmELoaybftRBUSErhqYiQzvEmuB").unwrap();
+    let wallet_sol_account =
+        spl_associated_token_account::get_associated_token_address(&wallet, &sol_mint_pubkey);
+    let pools = mint_pool_data.pools();
 
     let mut accounts = vec![
         AccountMeta::new(wallet, true), // 0. Wallet (signer)
@@ -176,140 +576,27 @@ fn create_swap_instruction(
         AccountMeta::new_readonly(associated_token_program_id, false), // 6. Associated Token program
     ];
 
-    // Determine the base mint for flashloan if needed
-    let flashloan_base_mint = if use_flashloan {
-        // For flashloan, we need a common base mint across all pools
-        // Check if all pools use SOL as base mint
-        let mut all_sol_base = true;
-        let mut all_usdc_base = true;
-
-        // Check all pool types to see their base mints
-        for pool in &mint_pool_data.raydium_pools {
-            if pool.base_mint != sol_mint_pubkey {
-                all_sol_base = false;
-            }
-            if pool.base_mint != usdc_mint {
-                all_usdc_base = false;
-            }
-        }
-        for pool in &mint_pool_data.raydium_cp_pools {
-            if pool.base_mint != sol_mint_pubkey {
-                all_sol_base = false;
-            }
-            if pool.base_mint != usdc_mint {
-                all_usdc_base = false;
-            }
-        }
-        // Add other pool type checks as needed...
-
-        if all_sol_base {
-            sol_mint_pubkey
-        } else if all_usdc_base {
-            usdc_mint
-        } else {
-            // Mixed base mints - default to SOL for now
-            sol_mint_pubkey
-        }
-    } else {
-        sol_mint_pubkey
-    };
-
     if use_flashloan {
-        let vault_authorities = [
-            Pubkey::from_str("5LFpzqgsxrSfhKwbaFiAEJ2kbc9QyimjKueswsyU4T3o").unwrap(),
-            Pubkey::from_str("4B2yxi8n7jr8w3K7cssokLNJZ6k2NjiwKwLdQ8L9dbAA").unwrap(),
-        ];
-        let vault_index = rand::random::<usize>() % vault_authorities.len();
-        let vault_authority = vault_authorities[vault_index];
-        accounts.push(AccountMeta::new_readonly(vault_authority, false));
-
-        let vault_token_account = if vault_index == 0 {
-            let token_pda = derive_vault_token_account(&executor_program_id, &flashloan_base_mint);
-            token_pda.0
-        } else {
-            spl_associated_token_account::get_associated_token_address(
-                &vault_authority,
-                &flashloan_base_mint,
-            )
-        };
-        accounts.push(AccountMeta::new(vault_token_account, false));
+        let base_mint = flashloan_base_mint(mint_pool_data, base_assets)?;
+        let vaults = flashloan_vaults(base_assets, &base_mint);
+        let (authority, token_account) = vaults[rand::random::<usize>() % vaults.len()];
+        accounts.push(AccountMeta::new_readonly(authority, false));
+        accounts.push(AccountMeta::new(token_account, false));
     }
 
-    // Check for mixed mode (USDC base)
-    let mut has_usdc_base = false;
-    let mut has_usd1_base = false;
-
-    // Check all pools to see if any have USDC as base mint
-    for pool in &mint_pool_data.raydium_pools {
-        if pool.base_mint == usdc_mint {
-            has_usdc_base = true;
-            break;
-        }
-    }
-    if !has_usdc_base {
-        for pool in &mint_pool_data.raydium_cp_pools {
-            if pool.base_mint == usdc_mint {
-                has_usdc_base = true;
-                break;
-            }
-        }
-    }
+    // Mixed mode: pools with a base other than SOL are bridged to SOL through the base asset's pool
+    if let Some(base_asset) = base_assets.bridged(pools.iter().map(|p| &p.base_mint)) {
+        let wallet_base_account =
+            spl_associated_token_account::get_associated_token_address(&wallet, &base_asset.mint);
 
-    if !has_usd1_base {
-        for pool in &mint_pool_data.raydium_pools {
-            if pool.base_mint == usd1_mint {
-                has_usd1_base = true;
-                break;
-            }
-        }
-    }
-    if !has_usd1_base {
-        for pool in &mint_pool_data.raydium_cp_pools {
-            if pool.base_mint == usd1_mint {
-                has_usd1_base = true;
-                break;
-            }
-        }
-    }
-    // Check other pool types as needed...
-
-    // If mixed mode is detected, add the required accounts
-    if has_usdc_base {
-        let wallet_usdc_account =
-            spl_associated_token_account::get_associated_token_address(&wallet, &usdc_mint);
-        let raydium_sol_usdc_pool =
-            Pubkey::from_str("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2").unwrap();
-        let raydium_usdc_vault =
-            Pubkey::from_str("HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz").unwrap();
-        let raydium_sol_vault =
-            Pubkey::from_str("DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz").unwrap();
-
-        accounts.push(AccountMeta::new_readonly(usdc_mint, false));
-        accounts.push(AccountMeta::new(wallet_usdc_account, false));
+        accounts.push(AccountMeta::new_readonly(base_asset.mint, false));
+        accounts.push(AccountMeta::new(wallet_base_account, false));
         accounts.push(AccountMeta::new_readonly(raydium_program_id(), false));
         accounts.push(AccountMeta::new_readonly(raydium_authority(), false));
         accounts.push(AccountMeta::new_readonly(sysvar_instructions, false));
-        accounts.push(AccountMeta::new(raydium_sol_usdc_pool, false));
-        accounts.push(AccountMeta::new(raydium_usdc_vault, false));
-        accounts.push(AccountMeta::new(raydium_sol_vault, false));
-    } else if has_usd1_base {
-        let wallet_usd1_account =
-            spl_associated_token_account::get_associated_token_address(&wallet, &usd1_mint);
-        let raydium_sol_usd1_pool =
-            Pubkey::from_str("FaDoeere161VKUFqcrQEM8it6kSCHKrLyq7wWyPvBkPq").unwrap();
-        let raydium_usd1_vault =
-            Pubkey::from_str("GLx7TdT66CPKYJBn3Pzc9khrfXEx6mXtAiE8uskGBQJq").unwrap();
-        let raydium_sol_vault =
-            Pubkey::from_str("3U9HB8KNHXmAmiGMbDsj6fBxzM63dfX5JbaYs5oTHbtu").unwrap();
-
-        accounts.push(AccountMeta::new_readonly(usd1_mint, false));
-        accounts.push(AccountMeta::new(wallet_usd1_account, false));
-        accounts.push(AccountMeta::new_readonly(raydium_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(raydium_authority(), false));
-        accounts.push(AccountMeta::new_readonly(sysvar_instructions, false));
-        accounts.push(AccountMeta::new(raydium_sol_usd1_pool, false));
-        accounts.push(AccountMeta::new(raydium_usd1_vault, false));
-        accounts.push(AccountMeta::new(raydium_sol_vault, false));
+        accounts.push(AccountMeta::new(base_asset.bridge_pool, false));
+        accounts.push(AccountMeta::new(base_asset.bridge_vault, false));
+        accounts.push(AccountMeta::new(base_asset.bridge_sol_vault, false));
     }
 
     // Add token mint and pools
@@ -375,7 +662,8 @@ fn create_swap_instruction(
         accounts.push(AccountMeta::new_readonly(global_volume_accumulator, false));
         accounts.push(AccountMeta::new(user_volume_accumulator, false));
 
-        let pump_fee_program_id = Pubkey::from_str("pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ").unwrap();
+        let pump_fee_program_id =
+            Pubkey::from_str("pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ").unwrap();
         let fee_config = Pubkey::from_str("5PHirr8joyTMp9JMm6nW7hNDVyEYdkzDqazxPD7RaTjx").unwrap();
         accounts.push(AccountMeta::new_readonly(fee_config, false));
         accounts.push(AccountMeta::new_readonly(pump_fee_program_id, false));
@@ -488,7 +776,7 @@ fn create_swap_instruction(
         accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
         accounts.push(AccountMeta::new(pool.pool, false));
         accounts.push(AccountMeta::new(pool.protocol_config, false)); // Protocol config is writable for Heaven
-        
+
         // Add fixed Heaven accounts
         accounts.push(AccountMeta::new_readonly(
             solana_program::sysvar::instructions::ID,
@@ -502,7 +790,7 @@ fn create_swap_instruction(
             heaven_protocol_account_2(),
             false,
         )); // Heaven protocol account 2
-        
+
         accounts.push(AccountMeta::new(pool.token_x_vault, false));
         accounts.push(AccountMeta::new(pool.token_base_vault, false));
     }
@@ -510,7 +798,6 @@ fn create_swap_instruction(
     // Create instruction data
     let mut data = vec![28u8];
 
-    let minimum_profit: u64 = 0;
     // When true, the bot will not fail the transaction even when it can't find a profitable arbitrage. It will just do nothing and succeed.
     let no_failure_mode = false;
 
@@ -0,0 +1,181 @@
+use crate::config::{WalletConfig, WalletRotation};
+use crate::keystore::{unlock, Keystore};
+use crate::remote_signer::RemoteSigner;
+use anyhow::Context;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Signs for a wallet: a local keypair, an unlocked keystore or a remote signing service.
+pub type WalletSigner = dyn Signer + Send + Sync;
+
+/// Prefix of wallet keys that are the URL of a remote signing service.
+const REMOTE_SIGNER_PREFIX: &str = "remote:";
+
+/// The wallets transactions are signed with. Every wallet trades from its own WSOL and token
+/// accounts, so transactions signed by different wallets don't write-lock the same accounts and
+/// the runtime doesn't have to run them one after another.
+pub struct Wallets {
+    keypairs: Vec<Arc<WalletSigner>>,
+    /// Pays the fees of the wallet at the same index instead of the wallet itself. Empty when
+    /// wallets pay their own fees.
+    fee_payers: Vec<Arc<WalletSigner>>,
+    rotation: WalletRotation,
+    next: AtomicUsize,
+    /// Wallet index of each mint, for per-mint rotation.
+    assigned: Mutex<HashMap<String, usize>>,
+}
+
+impl Wallets {
+    pub fn new(keypairs: Vec<Arc<WalletSigner>>, rotation: WalletRotation) -> anyhow::Result<Self> {
+        if keypairs.is_empty() {
+            anyhow::bail!("At least one wallet is required");
+        }
+        Ok(Self {
+            keypairs,
+            fee_payers: Vec::new(),
+            rotation,
+            next: AtomicUsize::new(0),
+            assigned: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn load(config: &WalletConfig) -> anyhow::Result<Self> {
+        let private_keys =
+            std::iter::once(&config.private_key).chain(config.extra_private_keys.iter().flatten());
+        let keypairs = private_keys
+            .enumerate()
+            .map(|(i, private_key)| {
+                load_signer(private_key)
+                    .with_context(|| format!("Failed to load wallet keypair {}", i))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let wallets = Self::new(keypairs, config.rotation.unwrap_or_default())?;
+        let fee_payer_keys = match (
+            &config.fee_payer_private_key,
+            &config.fee_payer_private_keys,
+        ) {
+            (Some(_), Some(_)) => anyhow::bail!(
+                "Set either wallet.fee_payer_private_key or wallet.fee_payer_private_keys"
+            ),
+            (Some(private_key), None) => vec![private_key.clone()],
+            (None, Some(private_keys)) => private_keys.clone(),
+            (None, None) => return Ok(wallets),
+        };
+        let fee_payers = fee_payer_keys
+            .iter()
+            .enumerate()
+            .map(|(i, private_key)| {
+                load_signer(private_key)
+                    .with_context(|| format!("Failed to load fee payer keypair {}", i))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        wallets.with_fee_payers(fee_payers)
+    }
+
+    /// Gives every wallet its own fee payer, in the order of the wallets. A fee payer is
+    /// writable in every transaction it pays for, so one shared by several wallets would make
+    /// the runtime run their transactions one after another again.
+    pub fn with_fee_payers(mut self, fee_payers: Vec<Arc<WalletSigner>>) -> anyhow::Result<Self> {
+        if fee_payers.len() != self.keypairs.len() {
+            anyhow::bail!(
+                "{} fee payers for {} wallets, every wallet needs its own fee payer",
+                fee_payers.len(),
+                self.keypairs.len()
+            );
+        }
+        let unique: HashSet<Pubkey> = fee_payers.iter().map(|f| f.pubkey()).collect();
+        if unique.len() != fee_payers.len() {
+            anyhow::bail!("Fee payers can't be shared between wallets");
+        }
+        self.fee_payers = fee_payers;
+        Ok(self)
+    }
+
+    /// The separate fee payers, one per wallet, if configured.
+    pub fn fee_payers(&self) -> &[Arc<WalletSigner>] {
+        &self.fee_payers
+    }
+
+    /// Who pays the fees of a transaction trading from `wallet`: its fee payer, or else the
+    /// wallet itself.
+    pub fn fee_payer_for(&self, wallet: &Arc<WalletSigner>) -> Arc<WalletSigner> {
+        self.keypairs
+            .iter()
+            .position(|k| k.pubkey() == wallet.pubkey())
+            .and_then(|i| self.fee_payers.get(i))
+            .unwrap_or(wallet)
+            .clone()
+    }
+
+    /// The first configured wallet. It owns the lookup tables and nonce accounts.
+    pub fn primary(&self) -> &Arc<WalletSigner> {
+        &self.keypairs[0]
+    }
+
+    pub fn all(&self) -> &[Arc<WalletSigner>] {
+        &self.keypairs
+    }
+
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        self.keypairs.iter().map(|k| k.pubkey()).collect()
+    }
+
+    /// The wallet to sign the next transaction for `mint` with. Per send, wallets are handed out
+    /// in turn, so sends made at the same time get different wallets as long as there are enough
+    /// of them. Per mint, each mint keeps the wallet it was first given.
+    pub fn for_send(&self, mint: &str) -> Arc<WalletSigner> {
+        let index = match self.rotation {
+            WalletRotation::PerSend => self.next_index(),
+            WalletRotation::PerMint => {
+                let mut assigned = self.assigned.lock().unwrap_or_else(|e| e.into_inner());
+                *assigned
+                    .entry(mint.to_string())
+                    .or_insert_with(|| self.next_index())
+            }
+        };
+        self.keypairs[index].clone()
+    }
+
+    fn next_index(&self) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % self.keypairs.len()
+    }
+}
+
+/// Loads the signer of a wallet key: `remote:<url>` for a remote signing service, a base58
+/// secret key, a keystore file or a keypair file.
+pub fn load_signer(private_key: &str) -> anyhow::Result<Arc<WalletSigner>> {
+    if let Some(url) = private_key.strip_prefix(REMOTE_SIGNER_PREFIX) {
+        return Ok(Arc::new(RemoteSigner::connect(url)?));
+    }
+    let path = Path::new(private_key);
+    if Keystore::is_keystore(path) {
+        let keystore = Keystore::read(path)
+            .with_context(|| format!("Failed to read keystore {}", path.display()))?;
+        return Ok(Arc::new(unlock(path, &keystore)?));
+    }
+    Ok(Arc::new(load_keypair(private_key)?))
+}
+
+/// Loads a base58 secret key or a keypair file.
+pub fn load_keypair(private_key: &str) -> anyhow::Result<Keypair> {
+    if let Ok(keypair) = bs58::decode(private_key)
+        .into_vec()
+        .map_err(|e| anyhow::anyhow!("Failed to decode base58: {}", e))
+        .and_then(|bytes| {
+            Keypair::from_bytes(&bytes).map_err(|e| anyhow::anyhow!("Invalid keypair bytes: {}", e))
+        })
+    {
+        return Ok(keypair);
+    }
+
+    if let Ok(keypair) = solana_sdk::signature::read_keypair_file(private_key) {
+        return Ok(keypair);
+    }
+
+    anyhow::bail!("Failed to load keypair from: {}", private_key)
+}
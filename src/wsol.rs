@@ -0,0 +1,227 @@
+use crate::config::WsolConfig;
+use crate::constants::sol_mint;
+use crate::ledger::{Ledger, LedgerEntry};
+use crate::rpc::AccountSource;
+use crate::rpc_pool::RpcPool;
+use crate::supervisor::Shutdown;
+use crate::wallets::WalletSigner;
+use solana_program::program_pack::Pack;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use spl_associated_token_account::get_associated_token_address;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Lamports kept on top of the configured reserve to pay for the wrap transaction itself.
+const WRAP_FEE_BUFFER: u64 = 100_000;
+
+/// Keeps the wallet WSOL account between the configured min and max balances.
+pub struct WsolManager {
+    rpc_pool: Arc<RpcPool>,
+    wallet_kp: Arc<WalletSigner>,
+    wsol_account: Pubkey,
+    config: WsolConfig,
+    ledger: Arc<Ledger>,
+}
+
+impl WsolManager {
+    pub fn new(
+        rpc_pool: Arc<RpcPool>,
+        wallet_kp: Arc<WalletSigner>,
+        config: WsolConfig,
+        ledger: Arc<Ledger>,
+    ) -> Self {
+        let wsol_account = get_associated_token_address(&wallet_kp.pubkey(), &sol_mint());
+        Self {
+            rpc_pool,
+            wallet_kp,
+            wsol_account,
+            config,
+            ledger,
+        }
+    }
+
+    /// Balance both wrap and unwrap move towards, halfway between min and max.
+    fn target_balance(&self) -> u64 {
+        self.config.min_balance + (self.config.max_balance - self.config.min_balance) / 2
+    }
+
+    pub fn ensure_account(&self) -> anyhow::Result<()> {
+        if self.rpc_pool.get_account(&self.wsol_account).is_ok() {
+            info!("WSOL account exists: {}", self.wsol_account);
+            return Ok(());
+        }
+
+        info!(
+            "WSOL account {} does not exist. Creating it...",
+            self.wsol_account
+        );
+        let wallet = self.wallet_kp.pubkey();
+        let create_ata_ix =
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &wallet,
+                &wallet,
+                &sol_mint(),
+                &spl_token::ID,
+            );
+        let signature = self.send(vec![create_ata_ix], &[])?;
+        info!("WSOL account created successfully! Signature: {}", signature);
+
+        self.ledger.record(LedgerEntry::WsolAccountCreated {
+            wallet: wallet.to_string(),
+            account: self.wsol_account.to_string(),
+            signature: signature.to_string(),
+        });
+        Ok(())
+    }
+
+    pub fn wsol_balance(&self) -> anyhow::Result<u64> {
+        let balance = self
+            .rpc_pool
+            .primary()
+            .get_token_account_balance(&self.wsol_account)?;
+        Ok(balance.amount.parse()?)
+    }
+
+    pub fn rebalance(&self) -> anyhow::Result<()> {
+        let wallet = self.wallet_kp.pubkey();
+        let wsol_balance = self.wsol_balance()?;
+        let native_balance = self.rpc_pool.primary().get_balance(&wallet)?;
+        let target = self.target_balance();
+
+        if wsol_balance < self.config.min_balance {
+            let wanted = target - wsol_balance;
+            let available =
+                native_balance.saturating_sub(self.config.native_reserve + WRAP_FEE_BUFFER);
+            let amount = wanted.min(available);
+
+            if amount == 0 {
+                warn!(
+                    "WSOL balance {} is below minimum {} but only {} lamports of native SOL are available (reserve {})",
+                    wsol_balance, self.config.min_balance, native_balance, self.config.native_reserve
+                );
+                return Ok(());
+            }
+            if amount < wanted {
+                warn!(
+                    "Wrapping only {} of the {} lamports needed to reach the WSOL target",
+                    amount, wanted
+                );
+            }
+
+            let signature = self.wrap(amount)?;
+            info!("Wrapped {} lamports into WSOL: {}", amount, signature);
+            self.ledger.record(LedgerEntry::Wrap {
+                wallet: wallet.to_string(),
+                amount,
+                wsol_balance_before: wsol_balance,
+                signature: signature.to_string(),
+            });
+        } else if wsol_balance > self.config.max_balance {
+            let amount = wsol_balance - target;
+
+            let signature = self.unwrap(amount)?;
+            info!("Unwrapped {} lamports of WSOL: {}", amount, signature);
+            self.ledger.record(LedgerEntry::Unwrap {
+                wallet: wallet.to_string(),
+                amount,
+                wsol_balance_before: wsol_balance,
+                signature: signature.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn wrap(&self, amount: u64) -> anyhow::Result<Signature> {
+        let wallet = self.wallet_kp.pubkey();
+        let instructions = vec![
+            system_instruction::transfer(&wallet, &self.wsol_account, amount),
+            spl_token::instruction::sync_native(&spl_token::ID, &self.wsol_account)?,
+        ];
+        self.send(instructions, &[])
+    }
+
+    /// Moves `amount` into a temporary WSOL account and closes it, so the
+    /// ATA used by the executor is never closed while the bot is running.
+    fn unwrap(&self, amount: u64) -> anyhow::Result<Signature> {
+        let wallet = self.wallet_kp.pubkey();
+        let temp_account = Keypair::new();
+        let account_len = spl_token::state::Account::LEN;
+        let rent = self
+            .rpc_pool
+            .primary()
+            .get_minimum_balance_for_rent_exemption(account_len)?;
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &wallet,
+                &temp_account.pubkey(),
+                rent,
+                account_len as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::ID,
+                &temp_account.pubkey(),
+                &sol_mint(),
+                &wallet,
+            )?,
+            spl_token::instruction::transfer(
+                &spl_token::ID,
+                &self.wsol_account,
+                &temp_account.pubkey(),
+                &wallet,
+                &[],
+                amount,
+            )?,
+            spl_token::instruction::close_account(
+                &spl_token::ID,
+                &temp_account.pubkey(),
+                &wallet,
+                &wallet,
+                &[],
+            )?,
+        ];
+        self.send(instructions, &[&temp_account])
+    }
+
+    fn send(
+        &self,
+        instructions: Vec<Instruction>,
+        extra_signers: &[&Keypair],
+    ) -> anyhow::Result<Signature> {
+        let mut all_instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
+            ComputeBudgetInstruction::set_compute_unit_limit(60_000),
+        ];
+        all_instructions.extend(instructions);
+
+        let mut signers: Vec<&dyn Signer> = vec![self.wallet_kp.as_ref()];
+        signers.extend(extra_signers.iter().map(|k| *k as &dyn Signer));
+
+        let blockhash = self.rpc_pool.primary().get_latest_blockhash()?;
+        let mut tx = Transaction::new_with_payer(&all_instructions, Some(&self.wallet_kp.pubkey()));
+        tx.try_sign(&signers, blockhash)?;
+        Ok(self.rpc_pool.primary().send_and_confirm_transaction(&tx)?)
+    }
+}
+
+pub async fn wsol_manager_loop(
+    manager: Arc<WsolManager>,
+    check_interval: Duration,
+    mut shutdown: Shutdown,
+) {
+    while !shutdown.is_triggered() {
+        if let Err(e) = manager.rebalance() {
+            error!("Failed to rebalance WSOL: {:?}", e);
+        }
+        shutdown.sleep(check_interval).await;
+    }
+}
@@ -0,0 +1,300 @@
+//! Runs the transaction the bot builds against the executor and dex programs in solana-program-test.
+//!
+//! Programs are read from `svm/programs/<program id>.so` and accounts from `svm/accounts/*.json`
+//! (`solana account <ADDRESS> --output json`). Every mint of `svm/scenarios.toml` is loaded with
+//! `initialize_pool_data` from those accounts, then its swap is built with
+//! `build_and_send_transaction` and executed. The test is ignored by default as it needs the
+//! program and account dumps; run it with `cargo test --manifest-path svm/Cargo.toml -- --ignored`.
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use common::{InMemoryAccounts, RecordingSender};
+use serde::Deserialize;
+use solana_onchain_arbitrage_bot::base_assets::BaseAssets;
+use solana_onchain_arbitrage_bot::config::{Config, FlashloanConfig, RoutingConfig};
+use solana_onchain_arbitrage_bot::refresh::initialize_pool_data;
+use solana_onchain_arbitrage_bot::rpc::{AccountSource, TxSender};
+use solana_onchain_arbitrage_bot::transaction::{build_and_send_transaction, PoolSelections, TxLifetime};
+use solana_program_test::ProgramTest;
+use solana_sdk::account::Account;
+use solana_sdk::bpf_loader;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::program_option::COption;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::TransactionError;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+const EXECUTOR_PROGRAM_ID: &str = "MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz";
+/// Balance of the payer's WSOL account, enough for any input amount the executor picks.
+const WSOL_BALANCE: u64 = 100_000_000_000;
+
+#[derive(Deserialize)]
+struct Scenarios {
+    #[serde(default)]
+    flashloan: bool,
+    /// Custom error codes of the swap instruction that still count as executed, e.g. the
+    /// executor rejecting an unprofitable route. Account layout errors are never allowed.
+    #[serde(default)]
+    allowed_errors: Vec<u32>,
+    routing: RoutingConfig,
+}
+
+fn svm_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf()
+}
+
+/// `<program id>.so` files of `dir`, keyed by program id.
+fn load_programs(dir: &Path) -> HashMap<Pubkey, Vec<u8>> {
+    let mut programs = HashMap::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return programs;
+    };
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "so") {
+            let stem = path.file_stem().unwrap().to_str().unwrap();
+            let program_id = Pubkey::from_str(stem).unwrap_or_else(|e| {
+                panic!("{} is not named after a program id: {}", path.display(), e)
+            });
+            programs.insert(program_id, fs::read(&path).unwrap());
+        }
+    }
+    programs
+}
+
+/// Accounts dumped with `solana account <ADDRESS> --output json`.
+fn load_accounts(dir: &Path) -> HashMap<Pubkey, Account> {
+    let mut accounts = HashMap::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return accounts;
+    };
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let (pubkey, account) = parse_cli_account(&fs::read_to_string(&path).unwrap())
+                .unwrap_or_else(|e| panic!("{}: {:?}", path.display(), e));
+            accounts.insert(pubkey, account);
+        }
+    }
+    accounts
+}
+
+fn parse_cli_account(json: &str) -> anyhow::Result<(Pubkey, Account)> {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct CliAccount {
+        lamports: u64,
+        data: (String, String),
+        owner: String,
+        executable: bool,
+        rent_epoch: u64,
+    }
+    #[derive(Deserialize)]
+    struct KeyedCliAccount {
+        pubkey: String,
+        account: CliAccount,
+    }
+
+    let keyed: KeyedCliAccount = serde_json::from_str(json)?;
+    if keyed.account.data.1 != "base64" {
+        anyhow::bail!("Expected base64 data, got {}", keyed.account.data.1);
+    }
+    Ok((
+        Pubkey::from_str(&keyed.pubkey)?,
+        Account {
+            lamports: keyed.account.lamports,
+            data: STANDARD.decode(&keyed.account.data.0)?,
+            owner: Pubkey::from_str(&keyed.account.owner)?,
+            executable: keyed.account.executable,
+            rent_epoch: keyed.account.rent_epoch,
+        },
+    ))
+}
+
+fn token_account(mint: &Pubkey, owner: &Pubkey, token_program: &Pubkey, amount: u64) -> Account {
+    const RENT: u64 = 2_039_280;
+    let is_native = *mint == spl_token::native_mint::ID;
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: if is_native {
+            COption::Some(RENT)
+        } else {
+            COption::None
+        },
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: if is_native { RENT + amount } else { RENT },
+        data,
+        owner: *token_program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+const CONFIG: &str = r#"
+[bot]
+compute_unit_limit = 1400000
+
+[routing]
+mint_config_list = []
+
+[rpc]
+url = "http://127.0.0.1:8899"
+
+[wallet]
+private_key = ""
+"#;
+
+#[tokio::test]
+#[ignore = "needs program and account dumps in svm/, see the SVM Tests section of the README"]
+async fn executor_accepts_built_transactions() {
+    let programs = load_programs(&svm_dir().join("programs"));
+    let executor = Pubkey::from_str(EXECUTOR_PROGRAM_ID).unwrap();
+    assert!(
+        programs.contains_key(&executor),
+        "No executor dump at svm/programs/{}.so, see the SVM Tests section of the README",
+        executor
+    );
+    let scenarios: Scenarios =
+        toml::from_str(&fs::read_to_string(svm_dir().join("scenarios.toml")).unwrap()).unwrap();
+    let dumped = load_accounts(&svm_dir().join("accounts"));
+
+    let mut config: Config = toml::from_str(CONFIG).unwrap();
+    config.flashloan = Some(FlashloanConfig {
+        enabled: scenarios.flashloan,
+    });
+
+    for mint_config in &scenarios.routing.mint_config_list {
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(true);
+        for (program_id, elf) in &programs {
+            program_test.add_account(
+                *program_id,
+                Account {
+                    lamports: 1_000_000_000,
+                    data: elf.clone(),
+                    owner: bpf_loader::id(),
+                    executable: true,
+                    rent_epoch: 0,
+                },
+            );
+        }
+        for (pubkey, account) in &dumped {
+            program_test.add_account(*pubkey, account.clone());
+        }
+        let mut context = program_test.start_with_context().await;
+        let payer = context.payer.insecure_clone();
+
+        // Pool loading reads the same accounts the bank was seeded with
+        let accounts = Arc::new(InMemoryAccounts::default());
+        for (pubkey, account) in &dumped {
+            accounts.insert(*pubkey, account.owner, account.data.clone());
+        }
+        let pool_data = initialize_pool_data(
+            &mint_config.mint,
+            &payer.pubkey().to_string(),
+            mint_config.raydium_pool_list.as_ref(),
+            mint_config.raydium_cp_pool_list.as_ref(),
+            mint_config.pump_pool_list.as_ref(),
+            mint_config.meteora_dlmm_pool_list.as_ref(),
+            mint_config.whirlpool_pool_list.as_ref(),
+            mint_config.raydium_clmm_pool_list.as_ref(),
+            mint_config.meteora_damm_pool_list.as_ref(),
+            mint_config.solfi_pool_list.as_ref(),
+            mint_config.meteora_damm_v2_pool_list.as_ref(),
+            mint_config.vertigo_pool_list.as_ref(),
+            mint_config.heaven_pool_list.as_ref(),
+            accounts.clone() as Arc<dyn AccountSource>,
+        )
+        .await
+        .unwrap_or_else(|e| panic!("Loading pools of {}: {:?}", mint_config.mint, e));
+
+        // The payer's token accounts: WSOL funds the swap, the others start empty
+        let mut wallet_mints: HashSet<(Pubkey, Pubkey)> = pool_data
+            .pools()
+            .iter()
+            .map(|p| (p.base_mint, spl_token::ID))
+            .collect();
+        wallet_mints.insert((pool_data.mint, pool_data.token_program));
+        wallet_mints.insert((spl_token::native_mint::ID, spl_token::ID));
+        for (mint, token_program) in wallet_mints {
+            let amount = if mint == spl_token::native_mint::ID {
+                WSOL_BALANCE
+            } else {
+                0
+            };
+            context.set_account(
+                &get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    &mint,
+                    &token_program,
+                ),
+                &token_account(&mint, &payer.pubkey(), &token_program, amount).into(),
+            );
+        }
+
+        let sender = Arc::new(RecordingSender::default());
+        build_and_send_transaction(
+            &payer,
+            &payer,
+            &config,
+            &BaseAssets::from_config(&config).unwrap(),
+            &pool_data,
+            &PoolSelections::default(),
+            0,
+            &[sender.clone() as Arc<dyn TxSender>],
+            TxLifetime::Blockhash(context.last_blockhash),
+            &[],
+        )
+        .await
+        .unwrap_or_else(|e| panic!("Building the swap of {}: {:?}", mint_config.mint, e));
+
+        let sent = sender.sent.lock().unwrap().clone();
+        for tx in sent {
+            let keys = tx.message.static_account_keys().to_vec();
+            let swap_index = tx.message.instructions().len() as u8 - 1;
+            let missing: Vec<&Pubkey> = keys
+                .iter()
+                .filter(|k| {
+                    !dumped.contains_key(k) && !programs.contains_key(k) && **k != payer.pubkey()
+                })
+                .collect();
+
+            let result = context
+                .banks_client
+                .process_transaction_with_metadata(tx)
+                .await
+                .unwrap();
+            let logs = result
+                .metadata
+                .map(|m| m.log_messages.join("\n"))
+                .unwrap_or_default();
+            match result.result {
+                Ok(()) => {}
+                Err(TransactionError::InstructionError(index, InstructionError::Custom(code)))
+                    if index == swap_index && scenarios.allowed_errors.contains(&code) => {}
+                Err(e) => panic!(
+                    "Swap of {} failed: {:?}\nAccounts not in svm/accounts (payer token accounts and builtins are expected): {:?}\n{}",
+                    mint_config.mint, e, missing, logs
+                ),
+            }
+        }
+    }
+}
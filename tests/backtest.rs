@@ -0,0 +1,108 @@
+//! Replays synthetic snapshots of two Raydium pools whose prices drift apart.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_onchain_arbitrage_bot::backtest::{run_backtest, transaction_fee};
+use solana_onchain_arbitrage_bot::config::MintConfig;
+use solana_onchain_arbitrage_bot::constants::sol_mint;
+use solana_onchain_arbitrage_bot::dex::raydium::raydium_program_id;
+use solana_onchain_arbitrage_bot::snapshot::AccountSnapshot;
+use solana_sdk::pubkey::Pubkey;
+
+struct Pool {
+    address: Pubkey,
+    token_vault: Pubkey,
+    sol_vault: Pubkey,
+}
+
+fn snapshot(slot: u64, address: &Pubkey, owner: &Pubkey, data: &[u8]) -> AccountSnapshot {
+    AccountSnapshot {
+        slot,
+        timestamp: slot * 400,
+        address: address.to_string(),
+        owner: owner.to_string(),
+        data: STANDARD.encode(data),
+    }
+}
+
+fn token_account(mint: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data
+}
+
+fn raydium_pool(mint: &Pubkey, slot: u64, snapshots: &mut Vec<AccountSnapshot>) -> Pool {
+    let pool = Pool {
+        address: Pubkey::new_unique(),
+        token_vault: Pubkey::new_unique(),
+        sol_vault: Pubkey::new_unique(),
+    };
+    let mut data = vec![0u8; 752];
+    data[336..368].copy_from_slice(pool.token_vault.as_ref());
+    data[368..400].copy_from_slice(pool.sol_vault.as_ref());
+    data[400..432].copy_from_slice(mint.as_ref());
+    data[432..464].copy_from_slice(sol_mint().as_ref());
+    snapshots.push(snapshot(slot, &pool.address, &raydium_program_id(), &data));
+    pool
+}
+
+fn balances(
+    slot: u64,
+    pool: &Pool,
+    mint: &Pubkey,
+    tokens: u64,
+    lamports: u64,
+    snapshots: &mut Vec<AccountSnapshot>,
+) {
+    snapshots.push(snapshot(
+        slot,
+        &pool.token_vault,
+        &spl_token::ID,
+        &token_account(mint, tokens),
+    ));
+    snapshots.push(snapshot(
+        slot,
+        &pool.sol_vault,
+        &spl_token::ID,
+        &token_account(&sol_mint(), lamports),
+    ));
+}
+
+#[tokio::test]
+async fn backtest_counts_opportunities_once_prices_diverge() {
+    let mint = Pubkey::new_unique();
+    let mut snapshots = vec![snapshot(1, &mint, &spl_token::ID, &[0u8; 82])];
+    let a = raydium_pool(&mint, 1, &mut snapshots);
+    let b = raydium_pool(&mint, 1, &mut snapshots);
+
+    // Same price on both pools, then 10% cheaper tokens on pool a
+    balances(1, &a, &mint, 1_000_000_000, 100_000_000_000, &mut snapshots);
+    balances(1, &b, &mint, 1_000_000_000, 100_000_000_000, &mut snapshots);
+    balances(2, &a, &mint, 1_100_000_000, 100_000_000_000, &mut snapshots);
+
+    let mint_config: MintConfig = toml::from_str(&format!(
+        "mint = \"{}\"\nraydium_pool_list = [\"{}\", \"{}\"]\nprocess_delay = 400",
+        mint, a.address, b.address
+    ))
+    .unwrap();
+    let report = run_backtest(&mint_config, &snapshots, 600_000, 1_000)
+        .await
+        .unwrap();
+
+    assert_eq!(report.first_slot, 1);
+    assert_eq!(report.last_slot, 2);
+    assert_eq!(report.fee_per_transaction, transaction_fee(600_000, 1_000));
+    assert_eq!(report.fee_per_transaction, 5_600);
+
+    let target = &report.targets[0];
+    assert_eq!(target.steps, 2);
+    assert_eq!(target.opportunities, 1);
+    assert_eq!(target.fees, report.fee_per_transaction);
+    let (slot, best) = target.best.as_ref().unwrap();
+    assert_eq!(*slot, 2);
+    assert_eq!(best.buy_pool, a.address);
+    assert_eq!(best.sell_pool, b.address);
+    assert_eq!(target.expected_profit, best.profit());
+    assert!(best.profit() > report.fee_per_transaction as i64);
+}
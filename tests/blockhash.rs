@@ -0,0 +1,72 @@
+//! The blockhash service against mock RPC clients: fallback, max age and the metrics file.
+
+use solana_client::rpc_client::RpcClient;
+use solana_onchain_arbitrage_bot::blockhash::BlockhashService;
+use solana_onchain_arbitrage_bot::config::BlockhashConfig;
+use solana_onchain_arbitrage_bot::rpc_pool::RpcPool;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn config(max_age: u64, metrics_path: Option<String>) -> BlockhashConfig {
+    BlockhashConfig {
+        refresh_slots: None,
+        poll_interval: None,
+        max_age: Some(max_age),
+        fallback_rpc_urls: None,
+        metrics_path,
+    }
+}
+
+/// A pool of mock clients, `"succeeds"` or `"fails"`.
+fn mock_pool(mocks: &[&str]) -> Arc<RpcPool> {
+    let clients = mocks
+        .iter()
+        .map(|mock| {
+            (
+                mock.to_string(),
+                Arc::new(RpcClient::new_mock(mock.to_string())),
+            )
+        })
+        .collect();
+    Arc::new(RpcPool::with_clients(clients, 25))
+}
+
+#[test]
+fn falls_back_to_the_next_rpc() {
+    let service = BlockhashService::new(
+        mock_pool(&["fails"]),
+        vec![Arc::new(RpcClient::new_mock("succeeds".to_string()))],
+        Some(&config(60_000, None)),
+    );
+    assert!(service.fresh().is_err());
+
+    let blockhash = service.refresh().unwrap();
+    assert_eq!(blockhash.last_valid_block_height, 1234);
+    assert_eq!(service.fresh().unwrap(), blockhash.hash);
+}
+
+#[test]
+fn refuses_blockhashes_over_the_max_age() {
+    let service = BlockhashService::new(mock_pool(&["succeeds"]), vec![], Some(&config(10, None)));
+    service.refresh().unwrap();
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(service.fresh().is_err());
+}
+
+#[test]
+fn writes_age_metrics() {
+    let path = std::env::temp_dir().join(format!("blockhash-{}.prom", std::process::id()));
+    let service = BlockhashService::new(
+        mock_pool(&["succeeds"]),
+        vec![],
+        Some(&config(60_000, Some(path.to_string_lossy().into_owned()))),
+    );
+    service.poll().unwrap();
+    service.write_metrics().unwrap();
+
+    let metrics = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(metrics.contains("blockhash_age_seconds "));
+    assert!(metrics.contains("blockhash_last_valid_block_height 1234"));
+}
@@ -0,0 +1,100 @@
+//! In-memory stand-ins for the RPC, so the pool loading and sending pipeline runs without a network.
+
+use anyhow::Context;
+use solana_onchain_arbitrage_bot::fixtures::AccountFixture;
+use solana_onchain_arbitrage_bot::rpc::{AccountSource, SendError, TxSender};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Accounts served from memory. Unknown accounts are reported as missing, like the RPC does.
+#[derive(Default)]
+pub struct InMemoryAccounts {
+    accounts: Mutex<HashMap<Pubkey, Account>>,
+}
+
+#[allow(dead_code)]
+impl InMemoryAccounts {
+    /// Every fixture under `dir`, as written by the `capture` command. Fails when there are
+    /// none, so a moved or emptied fixture directory doesn't leave the tests running on nothing.
+    pub fn from_fixtures(dir: &Path) -> anyhow::Result<Self> {
+        let accounts = Self::default();
+        let dex_dirs = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read fixtures from {}", dir.display()))?;
+        for dex_dir in dex_dirs {
+            let dex_dir = dex_dir?.path();
+            if !dex_dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&dex_dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    let fixture = AccountFixture::load(&path)?;
+                    accounts.insert(
+                        Pubkey::from_str(&fixture.address)?,
+                        Pubkey::from_str(&fixture.owner)?,
+                        fixture.account_data()?,
+                    );
+                }
+            }
+        }
+        if accounts.len() == 0 {
+            anyhow::bail!("No fixtures under {}", dir.display());
+        }
+        Ok(accounts)
+    }
+
+    pub fn insert(&self, address: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let account = Account {
+            lamports: 1_000_000_000,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.accounts.lock().unwrap().insert(address, account);
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.lock().unwrap().len()
+    }
+}
+
+impl AccountSource for InMemoryAccounts {
+    fn get_account(&self, pubkey: &Pubkey) -> anyhow::Result<Account> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .get(pubkey)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("AccountNotFound: pubkey={}", pubkey))
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> anyhow::Result<Vec<Option<Account>>> {
+        let accounts = self.accounts.lock().unwrap();
+        Ok(pubkeys.iter().map(|p| accounts.get(p).cloned()).collect())
+    }
+}
+
+/// Keeps every transaction it is given instead of sending it.
+#[derive(Default)]
+pub struct RecordingSender {
+    pub sent: Mutex<Vec<VersionedTransaction>>,
+}
+
+impl TxSender for RecordingSender {
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        _max_retries: u64,
+    ) -> Result<Signature, SendError> {
+        self.sent.lock().unwrap().push(tx.clone());
+        Ok(tx.signatures[0])
+    }
+}
@@ -0,0 +1,145 @@
+//! Config validation reporting every problem with its TOML path.
+
+use solana_onchain_arbitrage_bot::config::Config;
+use solana_sdk::pubkey::Pubkey;
+
+fn paths(config: &Config) -> Vec<String> {
+    config.problems().into_iter().map(|p| p.path).collect()
+}
+
+#[test]
+fn example_config_only_lacks_a_private_key() {
+    let config: Config = toml::from_str(include_str!("../config.toml.example")).unwrap();
+    assert_eq!(paths(&config), ["wallet.private_key"]);
+}
+
+#[test]
+fn every_problem_is_reported_with_its_path() {
+    let (mint, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let config: Config = toml::from_str(&format!(
+        r#"
+[bot]
+compute_unit_limit = 600000
+
+[routing]
+[[routing.mint_config_list]]
+mint = "{mint}"
+raydium_pool_list = ["{pool}", "not-a-pool"]
+process_delay = 0
+
+[[routing.mint_config_list]]
+mint = ""
+raydium_cp_pool_list = ["{pool}"]
+process_delay = 400
+
+[rpc]
+url = "$ARB_TEST_UNSET_RPC_URL"
+
+[spam]
+enabled = true
+sending_rpc_urls = []
+compute_unit_price = 1000
+
+[wallet]
+private_key = "wallet.json"
+"#
+    ))
+    .unwrap();
+
+    assert_eq!(
+        paths(&config),
+        [
+            "rpc.url",
+            "spam.sending_rpc_urls",
+            "routing.mint_config_list[0].process_delay",
+            "routing.mint_config_list[0].raydium_pool_list[1]",
+            "routing.mint_config_list[1].mint",
+            "routing.mint_config_list[1].raydium_cp_pool_list[0]",
+            "routing.mint_config_list[1]",
+        ]
+    );
+    let error = config.validate().unwrap_err().to_string();
+    assert!(error.contains("environment variable `ARB_TEST_UNSET_RPC_URL` is not set"));
+    assert!(error.contains("is already listed at routing.mint_config_list[0].raydium_pool_list[0]"));
+    assert!(error.contains("needs at least two pools to arbitrage between, found 1"));
+}
+
+#[test]
+fn mint_without_pools_is_reported() {
+    let mint = Pubkey::new_unique();
+    let mut config: Config = toml::from_str(include_str!("../config.toml.example")).unwrap();
+    config.wallet.private_key = "wallet.json".to_string();
+    config.routing.mint_config_list.truncate(1);
+    let mint_config = &mut config.routing.mint_config_list[0];
+    mint_config.mint = mint.to_string();
+    for list in [
+        &mut mint_config.raydium_pool_list,
+        &mut mint_config.raydium_cp_pool_list,
+        &mut mint_config.raydium_clmm_pool_list,
+        &mut mint_config.meteora_dlmm_pool_list,
+        &mut mint_config.meteora_damm_pool_list,
+        &mut mint_config.meteora_damm_v2_pool_list,
+        &mut mint_config.pump_pool_list,
+        &mut mint_config.whirlpool_pool_list,
+        &mut mint_config.solfi_pool_list,
+        &mut mint_config.vertigo_pool_list,
+        &mut mint_config.heaven_pool_list,
+    ] {
+        *list = Some(Vec::new());
+    }
+    mint_config.routes = None;
+
+    assert_eq!(paths(&config), ["routing.mint_config_list[0]"]);
+}
+
+#[test]
+fn shared_fee_payer_is_refused_with_extra_wallets() {
+    let mut config: Config = toml::from_str(include_str!("../config.toml.example")).unwrap();
+    config.wallet.private_key = "wallet.json".to_string();
+    config.wallet.extra_private_keys = Some(vec!["wallet-2.json".to_string()]);
+    config.wallet.fee_payer_private_key = Some("fee-payer.json".to_string());
+    assert_eq!(paths(&config), ["wallet.fee_payer_private_key"]);
+
+    config.wallet.fee_payer_private_key = None;
+    config.wallet.fee_payer_private_keys = Some(vec!["fee-payer.json".to_string()]);
+    assert_eq!(paths(&config), ["wallet.fee_payer_private_keys"]);
+
+    config
+        .wallet
+        .fee_payer_private_keys
+        .as_mut()
+        .unwrap()
+        .push("fee-payer-2.json".to_string());
+    assert!(paths(&config).is_empty());
+}
+
+#[test]
+fn nonce_and_wsol_settings_are_checked_up_front() {
+    let mut config: Config = toml::from_str(include_str!("../config.toml.example")).unwrap();
+    config.wallet.private_key = "wallet.json".to_string();
+    let nonce = config.nonce.as_mut().unwrap();
+    nonce.enabled = true;
+    nonce.count = 0;
+    let wsol = config.wsol.as_mut().unwrap();
+    wsol.enabled = true;
+    wsol.min_balance = wsol.max_balance + 1;
+    assert_eq!(paths(&config), ["nonce.count", "wsol.min_balance"]);
+
+    // Disabled sections are not checked
+    config.nonce.as_mut().unwrap().enabled = false;
+    config.wsol.as_mut().unwrap().enabled = false;
+    assert!(paths(&config).is_empty());
+}
+
+#[test]
+fn durable_nonces_are_refused_with_extra_wallets() {
+    let mut config: Config = toml::from_str(include_str!("../config.toml.example")).unwrap();
+    config.wallet.private_key = "wallet.json".to_string();
+    config.wallet.extra_private_keys = Some(vec!["wallet-2.json".to_string()]);
+    config.wallet.fee_payer_private_keys = Some(vec![
+        "fee-payer.json".to_string(),
+        "fee-payer-2.json".to_string(),
+    ]);
+    config.nonce.as_mut().unwrap().enabled = true;
+    assert_eq!(paths(&config), ["nonce.enabled"]);
+}
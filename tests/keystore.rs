@@ -0,0 +1,154 @@
+//! Encrypted keystores and the remote signing service protocol.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_onchain_arbitrage_bot::keystore::{KdfParams, Keystore, PASSPHRASE_ENV};
+use solana_onchain_arbitrage_bot::remote_signer::RemoteSigner;
+use solana_onchain_arbitrage_bot::wallets::load_signer;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+/// Cheap KDF parameters, so the tests stay fast.
+const TEST_KDF: KdfParams = KdfParams {
+    memory_kib: 64,
+    iterations: 1,
+    parallelism: 1,
+};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}.json", name, Keypair::new().pubkey()))
+}
+
+/// Serves the signing protocol on a local port, announcing `announced` and signing with
+/// `keypair`. Returns the base URL.
+fn serve_signer(announced: Pubkey, keypair: Keypair) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { break };
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = if request_line.starts_with("GET /pubkey") {
+                serde_json::json!({ "pubkey": announced.to_string() })
+            } else {
+                let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                let message = STANDARD
+                    .decode(request["message"].as_str().unwrap())
+                    .unwrap();
+                serde_json::json!({ "signature": keypair.sign_message(&message).to_string() })
+            };
+            let body = response.to_string();
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+    url
+}
+
+#[test]
+fn keystore_round_trips_with_the_right_passphrase_only() {
+    let keypair = Keypair::new();
+    let keystore = Keystore::encrypt(&keypair, "correct horse", TEST_KDF).unwrap();
+    assert_eq!(keystore.pubkey, keypair.pubkey().to_string());
+
+    let decrypted = keystore.decrypt("correct horse").unwrap();
+    assert_eq!(decrypted.to_bytes(), keypair.to_bytes());
+    assert!(keystore.decrypt("battery staple").is_err());
+
+    // The KDF and its parameters are read back from the file
+    let json = serde_json::to_value(&keystore).unwrap();
+    assert_eq!(json["kdf"], "argon2id");
+    assert_eq!(json["kdf_params"]["memory_kib"], 64);
+
+    let mut unknown_kdf = json;
+    unknown_kdf["kdf"] = "pbkdf2-sha256".into();
+    let unknown_kdf: Keystore = serde_json::from_value(unknown_kdf).unwrap();
+    assert!(unknown_kdf.decrypt("correct horse").is_err());
+}
+
+#[test]
+fn wallet_key_can_be_a_keystore_file() {
+    let keypair = Keypair::new();
+    let path = temp_path("keystore");
+    Keystore::encrypt(&keypair, "passphrase", TEST_KDF)
+        .unwrap()
+        .write(&path)
+        .unwrap();
+
+    std::env::set_var(PASSPHRASE_ENV, "passphrase");
+    let signer = load_signer(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(signer.unwrap().pubkey(), keypair.pubkey());
+}
+
+#[test]
+fn broken_keystore_file_reports_the_keystore_error() {
+    let path = temp_path("broken-keystore");
+    std::fs::write(&path, r#"{ "version": 2, "pubkey": "key" }"#).unwrap();
+    let error = load_signer(path.to_str().unwrap()).err().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(format!("{:#}", error).contains("Failed to read keystore"));
+    assert!(format!("{:#}", error).contains("missing field"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn remote_signer_signs_transactions_from_async_code() {
+    let keypair = Keypair::new();
+    let pubkey = keypair.pubkey();
+    let signer = load_signer(&format!("remote:{}", serve_signer(pubkey, keypair))).unwrap();
+    assert_eq!(signer.pubkey(), pubkey);
+
+    let instruction = system_instruction::transfer(&pubkey, &Keypair::new().pubkey(), 1);
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&pubkey));
+    tx.try_sign(&[signer.as_ref() as &dyn Signer], Hash::new_unique())
+        .unwrap();
+    assert!(tx.verify().is_ok());
+}
+
+#[tokio::test]
+async fn remote_signer_signs_on_a_current_thread_runtime() {
+    let keypair = Keypair::new();
+    let pubkey = keypair.pubkey();
+    let signer = RemoteSigner::connect(&serve_signer(pubkey, keypair)).unwrap();
+    let signature = signer.try_sign_message(b"message").unwrap();
+    assert!(signature.verify(pubkey.as_ref(), b"message"));
+}
+
+#[test]
+fn remote_signature_for_another_key_is_rejected() {
+    let announced = Keypair::new().pubkey();
+    let signer = RemoteSigner::connect(&serve_signer(announced, Keypair::new())).unwrap();
+    assert_eq!(signer.pubkey(), announced);
+    assert!(signer.try_sign_message(b"message").is_err());
+}
@@ -0,0 +1,608 @@
+//! Loads pools, builds the swap and "sends" it, all against in-memory accounts, some of them
+//! seeded from the decoder fixtures.
+
+mod common;
+
+use common::{InMemoryAccounts, RecordingSender};
+use solana_onchain_arbitrage_bot::base_assets::BaseAssets;
+use solana_onchain_arbitrage_bot::config::Config;
+use solana_onchain_arbitrage_bot::constants::sol_mint;
+use solana_onchain_arbitrage_bot::dex::error::ANCHOR_POOL_STATE_DISCRIMINATOR;
+use solana_onchain_arbitrage_bot::dex::raydium::{raydium_cp_program_id, raydium_program_id};
+use solana_onchain_arbitrage_bot::fixtures::AccountFixture;
+use solana_onchain_arbitrage_bot::nonce::NoncePool;
+use solana_onchain_arbitrage_bot::pools::{DexKind, MintPoolData};
+use solana_onchain_arbitrage_bot::refresh::initialize_pool_data;
+use solana_onchain_arbitrage_bot::rpc::{AccountSource, TxSender};
+use solana_onchain_arbitrage_bot::transaction::{
+    build_and_send_transaction, fee_collectors, flashloan_vaults, swap_lookup_accounts,
+    PoolSelections, TxLifetime,
+};
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::{Signer, SignerError};
+use solana_sdk::system_program;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const CONFIG: &str = r#"
+[bot]
+compute_unit_limit = 600000
+
+[routing]
+mint_config_list = []
+
+[rpc]
+url = "http://127.0.0.1:8899"
+
+[wallet]
+private_key = ""
+"#;
+
+fn token_account(mint: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// A Raydium AMM v4 pool of `mint` against SOL, with its two vaults.
+fn add_raydium_pool(accounts: &InMemoryAccounts, mint: &Pubkey) -> Pubkey {
+    let (pool, coin_vault, pc_vault) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut data = vec![0u8; 752];
+    data[336..368].copy_from_slice(coin_vault.as_ref());
+    data[368..400].copy_from_slice(pc_vault.as_ref());
+    data[400..432].copy_from_slice(mint.as_ref());
+    data[432..464].copy_from_slice(sol_mint().as_ref());
+    accounts.insert(pool, raydium_program_id(), data);
+    accounts.insert(coin_vault, spl_token::ID, token_account(mint, 1_000_000));
+    accounts.insert(
+        pc_vault,
+        spl_token::ID,
+        token_account(&sol_mint(), 5_000_000),
+    );
+    pool
+}
+
+/// A Raydium CP pool of SOL against `mint`, with its two vaults.
+fn add_raydium_cp_pool(accounts: &InMemoryAccounts, mint: &Pubkey) -> Pubkey {
+    let (pool, vault_0, vault_1) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut data = vec![0u8; 637];
+    data[..8].copy_from_slice(&ANCHOR_POOL_STATE_DISCRIMINATOR);
+    data[8..40].copy_from_slice(Pubkey::new_unique().as_ref());
+    data[72..104].copy_from_slice(vault_0.as_ref());
+    data[104..136].copy_from_slice(vault_1.as_ref());
+    data[168..200].copy_from_slice(sol_mint().as_ref());
+    data[200..232].copy_from_slice(mint.as_ref());
+    data[296..328].copy_from_slice(Pubkey::new_unique().as_ref());
+    accounts.insert(pool, raydium_cp_program_id(), data);
+    accounts.insert(
+        vault_0,
+        spl_token::ID,
+        token_account(&sol_mint(), 3_000_000),
+    );
+    accounts.insert(vault_1, spl_token::ID, token_account(mint, 700_000));
+    pool
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// The first fixture of `dex`.
+fn fixture(dex: DexKind) -> AccountFixture {
+    let path = fs::read_dir(fixtures_dir().join(dex.name()))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .unwrap_or_else(|| panic!("No {} fixture", dex.name()));
+    AccountFixture::load(&path).unwrap()
+}
+
+fn fixture_field(fixture: &AccountFixture, name: &str) -> Pubkey {
+    Pubkey::from_str(&fixture.expected[name]).unwrap()
+}
+
+/// A mint with a Raydium AMM and a Raydium CP pool, loaded from in-memory accounts.
+async fn load_two_pools(wallet: &Keypair) -> (MintPoolData, Pubkey, Pubkey) {
+    let accounts = Arc::new(InMemoryAccounts::from_fixtures(&fixtures_dir()).unwrap());
+    let mint = Pubkey::new_unique();
+    accounts.insert(mint, spl_token::ID, vec![0u8; 82]);
+
+    let raydium_pool = add_raydium_pool(&accounts, &mint);
+    let raydium_cp_pool = add_raydium_cp_pool(&accounts, &mint);
+    let raydium_pools = vec![raydium_pool.to_string()];
+    let raydium_cp_pools = vec![raydium_cp_pool.to_string()];
+
+    let pool_data = initialize_pool_data(
+        &mint.to_string(),
+        &wallet.pubkey().to_string(),
+        Some(&raydium_pools),
+        Some(&raydium_cp_pools),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        accounts.clone() as Arc<dyn AccountSource>,
+    )
+    .await
+    .unwrap();
+    (pool_data, raydium_pool, raydium_cp_pool)
+}
+
+#[tokio::test]
+async fn pools_load_build_and_send_without_rpc() {
+    let wallet = Keypair::new();
+    let (pool_data, raydium_pool, raydium_cp_pool) = load_two_pools(&wallet).await;
+    assert_eq!(pool_data.pools().len(), 2);
+    assert_eq!(pool_data.liquidity.get(&raydium_pool), Some(&5_000_000));
+    assert_eq!(pool_data.liquidity.get(&raydium_cp_pool), Some(&3_000_000));
+
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let sender = Arc::new(RecordingSender::default());
+    let sent_txs = build_and_send_transaction(
+        &wallet,
+        &wallet,
+        &config,
+        &BaseAssets::from_config(&config).unwrap(),
+        &pool_data,
+        &PoolSelections::default(),
+        0,
+        &[sender.clone() as Arc<dyn TxSender>],
+        TxLifetime::Blockhash(Hash::new_unique()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let sent = sender.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent_txs.len(), 1);
+    assert_eq!(sent_txs[0].signature, sent[0].signatures[0]);
+    assert_eq!(sent_txs[0].nonce, None);
+
+    let message = &sent[0].message;
+    let keys = message.static_account_keys();
+    assert_eq!(keys[0], wallet.pubkey());
+    for pool in [raydium_pool, raydium_cp_pool] {
+        assert!(keys.contains(&pool), "{} is not in the transaction", pool);
+    }
+    let swap = message.instructions().last().unwrap();
+    assert_eq!(
+        keys[swap.program_id_index as usize],
+        Pubkey::from_str("MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz").unwrap()
+    );
+}
+
+#[tokio::test]
+async fn lookup_accounts_list_every_fee_collector_and_vault() {
+    let wallet = Keypair::new();
+    let (pool_data, raydium_pool, raydium_cp_pool) = load_two_pools(&wallet).await;
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let base_assets = BaseAssets::from_config(&config).unwrap();
+
+    for use_flashloan in [false, true] {
+        let accounts =
+            swap_lookup_accounts(&wallet, &pool_data, &base_assets, 600_000, use_flashloan)
+                .unwrap();
+        assert_eq!(
+            accounts,
+            swap_lookup_accounts(&wallet, &pool_data, &base_assets, 600_000, use_flashloan)
+                .unwrap()
+        );
+        assert!(!accounts.contains(&wallet.pubkey()));
+        let mut expected = vec![raydium_pool, raydium_cp_pool];
+        expected.extend(fee_collectors(use_flashloan));
+        if use_flashloan {
+            for (authority, token_account) in flashloan_vaults(&base_assets, &sol_mint()) {
+                expected.extend([authority, token_account]);
+            }
+        }
+        for pubkey in expected {
+            assert!(accounts.contains(&pubkey), "{} is not listed", pubkey);
+        }
+    }
+}
+
+#[tokio::test]
+async fn fixture_pools_load_build_and_send() {
+    let accounts = Arc::new(InMemoryAccounts::from_fixtures(&fixtures_dir()).unwrap());
+    let raydium = fixture(DexKind::Raydium);
+    let raydium_cp = fixture(DexKind::RaydiumCp);
+
+    // Fixtures hold the pool accounts only, the mint and vaults are added here
+    let mint = fixture_field(&raydium, "coin_mint");
+    accounts.insert(mint, spl_token::ID, vec![0u8; 82]);
+    let vaults = [
+        (fixture_field(&raydium, "coin_vault"), mint, 1_000_000),
+        (fixture_field(&raydium, "pc_vault"), sol_mint(), 5_000_000),
+        (
+            fixture_field(&raydium_cp, "token_0_vault"),
+            sol_mint(),
+            3_000_000,
+        ),
+        (fixture_field(&raydium_cp, "token_1_vault"), mint, 700_000),
+    ];
+    for (vault, vault_mint, amount) in vaults {
+        accounts.insert(vault, spl_token::ID, token_account(&vault_mint, amount));
+    }
+
+    let wallet = Keypair::new();
+    let pool_data = initialize_pool_data(
+        &mint.to_string(),
+        &wallet.pubkey().to_string(),
+        Some(&vec![raydium.address.clone()]),
+        Some(&vec![raydium_cp.address.clone()]),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        accounts.clone() as Arc<dyn AccountSource>,
+    )
+    .await
+    .unwrap();
+
+    let pools = pool_data.pools();
+    let vaults_of = |fixture: &AccountFixture| {
+        let pool = pools
+            .iter()
+            .find(|pool| pool.pool.to_string() == fixture.address)
+            .unwrap();
+        (pool.token_vault, pool.base_vault)
+    };
+    assert_eq!(pools.len(), 2);
+    assert_eq!(vaults_of(&raydium), (vaults[0].0, vaults[1].0));
+    assert_eq!(vaults_of(&raydium_cp), (vaults[3].0, vaults[2].0));
+
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let sender = Arc::new(RecordingSender::default());
+    build_and_send_transaction(
+        &wallet,
+        &wallet,
+        &config,
+        &BaseAssets::from_config(&config).unwrap(),
+        &pool_data,
+        &PoolSelections::default(),
+        0,
+        &[sender.clone() as Arc<dyn TxSender>],
+        TxLifetime::Blockhash(Hash::new_unique()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let sent = sender.sent.lock().unwrap();
+    let keys = sent[0].message.static_account_keys();
+    for fixture in [&raydium, &raydium_cp] {
+        let pool = Pubkey::from_str(&fixture.address).unwrap();
+        assert!(keys.contains(&pool), "{} is not in the transaction", pool);
+    }
+}
+
+#[test]
+fn missing_fixture_directory_fails_to_load() {
+    assert!(InMemoryAccounts::from_fixtures(&fixtures_dir().join("missing")).is_err());
+    assert!(InMemoryAccounts::from_fixtures(&fixtures_dir().join("raydium")).is_err());
+}
+
+#[tokio::test]
+async fn separate_fee_payer_pays_and_signs() {
+    let wallet = Keypair::new();
+    let fee_payer = Keypair::new();
+    let (pool_data, _, _) = load_two_pools(&wallet).await;
+
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let sender = Arc::new(RecordingSender::default());
+    build_and_send_transaction(
+        &wallet,
+        &fee_payer,
+        &config,
+        &BaseAssets::from_config(&config).unwrap(),
+        &pool_data,
+        &PoolSelections::default(),
+        0,
+        &[sender.clone() as Arc<dyn TxSender>],
+        TxLifetime::Blockhash(Hash::new_unique()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let sent = sender.sent.lock().unwrap();
+    let tx = &sent[0];
+    let keys = tx.message.static_account_keys();
+    assert_eq!(keys[0], fee_payer.pubkey());
+    assert_eq!(keys[1], wallet.pubkey());
+    assert_eq!(tx.signatures.len(), 2);
+    assert!(tx.verify_with_results().iter().all(|ok| *ok));
+}
+
+#[tokio::test]
+async fn nonce_mode_advances_a_different_nonce_per_transaction() {
+    let wallet = Keypair::new();
+    let (pool_data, _, _) = load_two_pools(&wallet).await;
+    let nonces = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let nonce_pool = NoncePool::new(wallet.pubkey(), nonces.to_vec(), Duration::from_secs(60));
+    let values = [Hash::new_unique(), Hash::new_unique()];
+    for (address, value) in nonces.iter().zip(values) {
+        nonce_pool.update(address, value);
+    }
+
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let sender = Arc::new(RecordingSender::default());
+    for i in 0..2 {
+        let sent_txs = build_and_send_transaction(
+            &wallet,
+            &wallet,
+            &config,
+            &BaseAssets::from_config(&config).unwrap(),
+            &pool_data,
+            &PoolSelections::default(),
+            0,
+            &[sender.clone() as Arc<dyn TxSender>],
+            TxLifetime::Nonce(&nonce_pool),
+            &[],
+        )
+        .await
+        .unwrap();
+        assert_eq!(sent_txs[0].nonce, Some((nonces[i], values[i])));
+    }
+
+    {
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        for (i, tx) in sent.iter().enumerate() {
+            let message = &tx.message;
+            let keys = message.static_account_keys();
+            assert_eq!(*message.recent_blockhash(), values[i]);
+            let advance = &message.instructions()[0];
+            assert_eq!(
+                keys[advance.program_id_index as usize],
+                system_program::id()
+            );
+            assert_eq!(keys[advance.accounts[0] as usize], nonces[i]);
+        }
+    }
+
+    // Both nonces wait for the chain to show them advanced
+    let result = build_and_send_transaction(
+        &wallet,
+        &wallet,
+        &config,
+        &BaseAssets::from_config(&config).unwrap(),
+        &pool_data,
+        &PoolSelections::default(),
+        0,
+        &[sender.clone() as Arc<dyn TxSender>],
+        TxLifetime::Nonce(&nonce_pool),
+        &[],
+    )
+    .await;
+    assert!(result.is_err());
+    nonce_pool.update(&nonces[0], Hash::new_unique());
+    assert_eq!(
+        nonce_pool.acquire().map(|(address, _)| address),
+        Some(nonces[0])
+    );
+}
+
+/// Signs with a keypair and counts the signatures it was asked for.
+struct CountingSigner {
+    keypair: Keypair,
+    count: AtomicUsize,
+}
+
+impl Signer for CountingSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.keypair.pubkey())
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.keypair.try_sign_message(message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+/// A mint with `count` Raydium AMM pools, too many for one transaction when `count` is 20.
+async fn load_raydium_pools(wallet: &Pubkey, count: usize) -> MintPoolData {
+    let accounts = Arc::new(InMemoryAccounts::default());
+    let mint = Pubkey::new_unique();
+    accounts.insert(mint, spl_token::ID, vec![0u8; 82]);
+    let raydium_pools: Vec<String> = (0..count)
+        .map(|_| add_raydium_pool(&accounts, &mint).to_string())
+        .collect();
+    initialize_pool_data(
+        &mint.to_string(),
+        &wallet.to_string(),
+        Some(&raydium_pools),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        accounts as Arc<dyn AccountSource>,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn only_the_sent_transaction_is_signed() {
+    let wallet = CountingSigner {
+        keypair: Keypair::new(),
+        count: AtomicUsize::new(0),
+    };
+    // Pools are left out one compile at a time until the transaction fits
+    let pool_data = load_raydium_pools(&wallet.pubkey(), 20).await;
+
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let sender = Arc::new(RecordingSender::default());
+    build_and_send_transaction(
+        &wallet,
+        &wallet,
+        &config,
+        &BaseAssets::from_config(&config).unwrap(),
+        &pool_data,
+        &PoolSelections::default(),
+        0,
+        &[sender.clone() as Arc<dyn TxSender>],
+        TxLifetime::Blockhash(Hash::new_unique()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let sent = sender.sent.lock().unwrap();
+    assert!(sent[0].message.static_account_keys().len() < 64);
+    assert!(sent[0].verify_with_results().iter().all(|ok| *ok));
+    assert_eq!(wallet.count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn pools_picked_to_fit_are_kept_until_the_ranking_changes() {
+    let wallet = Keypair::new();
+    let pool_data = load_raydium_pools(&wallet.pubkey(), 20).await;
+    let pool_selections = PoolSelections::default();
+
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    let sender = Arc::new(RecordingSender::default());
+    for _ in 0..2 {
+        build_and_send_transaction(
+            &wallet,
+            &wallet,
+            &config,
+            &BaseAssets::from_config(&config).unwrap(),
+            &pool_data,
+            &pool_selections,
+            0,
+            &[sender.clone() as Arc<dyn TxSender>],
+            TxLifetime::Blockhash(Hash::new_unique()),
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    let selected = pool_selections.get(&pool_data).unwrap();
+    assert!(selected.len() >= 2 && selected.len() < 20);
+    for tx in sender.sent.lock().unwrap().iter() {
+        let keys = tx.message.static_account_keys();
+        for pool in pool_data.pools_by_liquidity() {
+            assert_eq!(keys.contains(&pool), selected.contains(&pool));
+        }
+    }
+
+    let mut reranked = pool_data.clone();
+    let last = *pool_data.pools_by_liquidity().last().unwrap();
+    reranked.liquidity.insert(last, u64::MAX);
+    assert!(pool_selections.get(&reranked).is_none());
+}
+
+#[test]
+fn liquidity_is_only_compared_on_the_same_base() {
+    let (sol, usdc) = (sol_mint(), Pubkey::new_unique());
+    let mut pool_data = MintPoolData::new(
+        &Pubkey::new_unique().to_string(),
+        &Pubkey::new_unique().to_string(),
+        spl_token::ID,
+    )
+    .unwrap();
+    // 5 SOL, 1 SOL and 1,000 USDC in raw amounts: the USDC pool holds the most units
+    let pools = [
+        (sol, 5_000_000_000),
+        (sol, 1_000_000_000),
+        (usdc, 1_000_000_000),
+    ]
+    .map(|(base_mint, amount)| {
+        let pool = Pubkey::new_unique();
+        pool_data
+            .add_raydium_pool(
+                &pool.to_string(),
+                &Pubkey::new_unique().to_string(),
+                &Pubkey::new_unique().to_string(),
+                &pool_data.mint.to_string(),
+                &base_mint.to_string(),
+            )
+            .unwrap();
+        pool_data.liquidity.insert(pool, amount);
+        pool
+    });
+    let unknown = Pubkey::new_unique();
+    pool_data
+        .add_raydium_pool(
+            &unknown.to_string(),
+            &Pubkey::new_unique().to_string(),
+            &Pubkey::new_unique().to_string(),
+            &pool_data.mint.to_string(),
+            &usdc.to_string(),
+        )
+        .unwrap();
+
+    let by_liquidity = pool_data.pools_by_liquidity();
+    assert_eq!(by_liquidity.len(), 4);
+    let mut deepest = by_liquidity[..2].to_vec();
+    deepest.sort();
+    let mut expected = vec![pools[0], pools[2]];
+    expected.sort();
+    assert_eq!(deepest, expected);
+    assert_eq!(by_liquidity[2..], [pools[1], unknown]);
+}
+
+#[tokio::test]
+async fn missing_pool_account_fails_to_load() {
+    let accounts = Arc::new(InMemoryAccounts::default());
+    let mint = Pubkey::new_unique();
+    accounts.insert(mint, spl_token::ID, vec![0u8; 82]);
+    let raydium_pools = vec![Pubkey::new_unique().to_string()];
+
+    let result = initialize_pool_data(
+        &mint.to_string(),
+        &Pubkey::new_unique().to_string(),
+        Some(&raydium_pools),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        accounts as Arc<dyn AccountSource>,
+    )
+    .await;
+    assert!(result.is_err());
+}
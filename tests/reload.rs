@@ -0,0 +1,108 @@
+//! Sorting the mints of a reloaded routing config by what has to happen to their tasks.
+
+use solana_onchain_arbitrage_bot::config::{MintConfig, RoutingConfig};
+use solana_onchain_arbitrage_bot::reload::diff_routing;
+
+const CURRENT: &str = r#"
+[[mint_config_list]]
+mint = "kept"
+raydium_pool_list = ["pool-a"]
+process_delay = 400
+
+[[mint_config_list]]
+mint = "retimed"
+raydium_pool_list = ["pool-b"]
+process_delay = 400
+
+[[mint_config_list]]
+mint = "repooled"
+raydium_pool_list = ["pool-c"]
+process_delay = 400
+
+[[mint_config_list]]
+mint = "dropped"
+raydium_pool_list = ["pool-d"]
+process_delay = 400
+"#;
+
+fn routing(toml: &str) -> RoutingConfig {
+    toml::from_str(toml).unwrap()
+}
+
+fn mints(configs: &[MintConfig]) -> Vec<&str> {
+    configs.iter().map(|m| m.mint.as_str()).collect()
+}
+
+#[test]
+fn mints_are_added_removed_reinitialized_or_updated() {
+    let new = routing(
+        r#"
+[[mint_config_list]]
+mint = "kept"
+raydium_pool_list = ["pool-a"]
+process_delay = 400
+
+[[mint_config_list]]
+mint = "retimed"
+raydium_pool_list = ["pool-b"]
+process_delay = 200
+use_tpu = false
+
+[[mint_config_list]]
+mint = "repooled"
+raydium_pool_list = ["pool-c", "pool-e"]
+process_delay = 400
+
+[[mint_config_list]]
+mint = "new"
+raydium_pool_list = ["pool-f"]
+process_delay = 400
+"#,
+    );
+
+    let changes = diff_routing(&routing(CURRENT), &new);
+    assert_eq!(mints(&changes.added), ["new"]);
+    assert_eq!(changes.removed, ["dropped"]);
+    assert_eq!(mints(&changes.reinitialized), ["repooled"]);
+    assert_eq!(mints(&changes.updated), ["retimed"]);
+    assert_eq!(changes.updated[0].process_delay, 200);
+}
+
+#[test]
+fn route_changes_keep_the_pools_but_lookup_table_changes_reload_them() {
+    let new = routing(
+        r#"
+[[mint_config_list]]
+mint = "kept"
+raydium_pool_list = ["pool-a"]
+process_delay = 400
+routes = [{ name = "a", pools = ["pool-a"], minimum_profit = 1000 }]
+
+[[mint_config_list]]
+mint = "retimed"
+raydium_pool_list = ["pool-b"]
+process_delay = 400
+lookup_table_accounts = ["table"]
+
+[[mint_config_list]]
+mint = "repooled"
+raydium_pool_list = ["pool-c"]
+process_delay = 400
+
+[[mint_config_list]]
+mint = "dropped"
+raydium_pool_list = ["pool-d"]
+process_delay = 400
+"#,
+    );
+
+    let changes = diff_routing(&routing(CURRENT), &new);
+    assert_eq!(mints(&changes.updated), ["kept"]);
+    assert_eq!(mints(&changes.reinitialized), ["retimed"]);
+    assert!(changes.added.is_empty() && changes.removed.is_empty());
+}
+
+#[test]
+fn same_routing_has_no_changes() {
+    assert!(diff_routing(&routing(CURRENT), &routing(CURRENT)).is_empty());
+}
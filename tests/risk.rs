@@ -0,0 +1,146 @@
+//! Settling the PnL of a landed transaction for the wallet that traded and its fee payer, and
+//! following sent transactions until they land, expire or their durable nonce advances.
+
+use serde_json::json;
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_onchain_arbitrage_bot::constants::SOL_MINT;
+use solana_onchain_arbitrage_bot::ledger::Ledger;
+use solana_onchain_arbitrage_bot::nonce::read_nonce;
+use solana_onchain_arbitrage_bot::risk::{wallet_pnl, RiskManager};
+use solana_onchain_arbitrage_bot::rpc_pool::RpcPool;
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::nonce::state::{Data, DurableNonce, State, Versions};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::system_program;
+use solana_transaction_status::UiTransactionStatusMeta;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn wsol_balance(account_index: u8, owner: &Pubkey, amount: u64) -> serde_json::Value {
+    json!({
+        "accountIndex": account_index,
+        "mint": SOL_MINT,
+        "owner": owner.to_string(),
+        "uiTokenAmount": {
+            "uiAmount": null,
+            "decimals": 9,
+            "amount": amount.to_string(),
+            "uiAmountString": "0",
+        },
+    })
+}
+
+/// A transaction paid by account 0 that moved the WSOL of account 2, owned by `wallet`, from
+/// 1_000_000 to 1_030_000 lamports.
+fn meta(
+    pre_balances: [u64; 3],
+    post_balances: [u64; 3],
+    wallet: &Pubkey,
+) -> UiTransactionStatusMeta {
+    serde_json::from_value(json!({
+        "err": null,
+        "status": { "Ok": null },
+        "fee": 5_000,
+        "preBalances": pre_balances,
+        "postBalances": post_balances,
+        "preTokenBalances": [wsol_balance(2, wallet, 1_000_000)],
+        "postTokenBalances": [wsol_balance(2, wallet, 1_030_000)],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn wallet_paying_its_own_fees() {
+    let wallet = Pubkey::new_unique();
+    let keys = [wallet, Pubkey::new_unique(), Pubkey::new_unique()];
+    let meta = meta([100_000, 0, 0], [95_000, 0, 0], &wallet);
+    assert_eq!(wallet_pnl(&meta, &keys, &wallet), 25_000);
+}
+
+#[test]
+fn separate_fee_payer_counts_the_fee() {
+    let fee_payer = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+    let keys = [fee_payer, wallet, Pubkey::new_unique()];
+    let fee_only = meta([100_000, 50_000, 0], [95_000, 50_000, 0], &wallet);
+    assert_eq!(wallet_pnl(&fee_only, &keys, &wallet), 25_000);
+
+    // SOL the wallet itself spends is read from its own index
+    let wallet_spent = meta([100_000, 50_000, 0], [95_000, 40_000, 0], &wallet);
+    assert_eq!(wallet_pnl(&wallet_spent, &keys, &wallet), 15_000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn nonce_sends_stay_pending_until_the_nonce_advances() {
+    let wallet = Pubkey::new_unique();
+    let nonce_address = Pubkey::new_unique();
+    let state = Versions::new(State::Initialized(Data::new(
+        wallet,
+        DurableNonce::from_blockhash(&Hash::new_unique()),
+        5_000,
+    )));
+    let account = Account::new_data(1_447_680, &state, &system_program::id()).unwrap();
+    let nonce = read_nonce(&account, &wallet).unwrap();
+
+    // The mock knows no signature. The first settlement reads the nonce unchanged, later ones
+    // find no nonce account.
+    let encoded = UiAccount::encode(
+        &nonce_address,
+        &account,
+        UiAccountEncoding::Base64,
+        None,
+        None,
+    );
+    let mocks = HashMap::from([(
+        RpcRequest::GetMultipleAccounts,
+        json!({ "context": { "slot": 1 }, "value": [encoded] }),
+    )]);
+    let client = RpcClient::new_mock_with_mocks("sig_not_found".to_string(), mocks);
+    let rpc_pool = Arc::new(RpcPool::with_clients(
+        vec![("mock".to_string(), Arc::new(client))],
+        25,
+    ));
+    let ledger_path = std::env::temp_dir().join(format!("ledger-{}.jsonl", wallet));
+    let ledger = Arc::new(Ledger::open(ledger_path.to_str().unwrap()).unwrap());
+    let risk = RiskManager::new(None, rpc_pool, vec![wallet], ledger).unwrap();
+
+    risk.track(
+        Signature::new_unique(),
+        "mint",
+        wallet,
+        Some((nonce_address, nonce)),
+    );
+    risk.wait_for_pending(Duration::from_millis(100)).await;
+    assert_eq!(risk.pending_count(), 1);
+
+    risk.wait_for_pending(Duration::from_millis(100)).await;
+    assert_eq!(risk.pending_count(), 0);
+    std::fs::remove_file(&ledger_path).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn one_settlement_checks_every_pending_send() {
+    // Every signature the mock is asked about is finalized
+    let client = RpcClient::new_mock("succeeds".to_string());
+    let rpc_pool = Arc::new(RpcPool::with_clients(
+        vec![("mock".to_string(), Arc::new(client))],
+        25,
+    ));
+    let wallet = Pubkey::new_unique();
+    let ledger_path = std::env::temp_dir().join(format!("ledger-{}.jsonl", wallet));
+    let ledger = Arc::new(Ledger::open(ledger_path.to_str().unwrap()).unwrap());
+    let risk = RiskManager::new(None, rpc_pool, vec![wallet], ledger).unwrap();
+
+    // More than one getSignatureStatuses request can hold
+    for _ in 0..600 {
+        risk.track(Signature::new_unique(), "mint", wallet, None);
+    }
+    risk.wait_for_pending(Duration::from_millis(100)).await;
+    assert_eq!(risk.pending_count(), 0);
+    std::fs::remove_file(&ledger_path).unwrap();
+}
@@ -0,0 +1,72 @@
+//! Health scores of read endpoints and moving reads to the healthiest one.
+
+use solana_client::rpc_client::RpcClient;
+use solana_onchain_arbitrage_bot::rpc_pool::{
+    endpoint_label, pick_primary, EndpointHealth, RpcPool,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn healthy(slot: u64, latency_ms: u64) -> EndpointHealth {
+    let mut health = EndpointHealth::default();
+    health.record_success(Duration::from_millis(latency_ms));
+    health.slot = Some(slot);
+    health
+}
+
+#[test]
+fn lagging_failing_and_slow_endpoints_score_lower() {
+    let fast = healthy(1_000, 20).score(1_000, 25);
+    let slow = healthy(1_000, 400).score(1_000, 25);
+    let lagging = healthy(990, 20).score(1_000, 25);
+    assert!(fast > slow && fast > lagging);
+    assert!(fast <= 100.0);
+
+    assert_eq!(healthy(970, 20).score(1_000, 25), 0.0);
+    assert_eq!(EndpointHealth::default().score(1_000, 25), 0.0);
+
+    let mut failing = healthy(1_000, 20);
+    failing.record_failure();
+    assert_eq!(failing.score(1_000, 25), 0.0);
+    failing.record_success(Duration::from_millis(20));
+    let recovered = failing.score(1_000, 25);
+    assert!(recovered > 0.0 && recovered < fast);
+}
+
+#[test]
+fn primary_only_moves_for_a_clearly_better_endpoint() {
+    assert_eq!(pick_primary(&[50.0, 52.0], 0), 0);
+    assert_eq!(pick_primary(&[50.0, 80.0], 0), 1);
+    assert_eq!(pick_primary(&[0.0, 1.0], 0), 1);
+    assert_eq!(pick_primary(&[0.0, 0.0], 1), 1);
+}
+
+#[test]
+fn reads_move_off_a_failing_endpoint() {
+    let clients = ["fails", "succeeds"]
+        .iter()
+        .map(|mock| {
+            (
+                mock.to_string(),
+                Arc::new(RpcClient::new_mock(mock.to_string())),
+            )
+        })
+        .collect();
+    let pool = RpcPool::with_clients(clients, 25);
+    assert!(pool.primary().get_slot().is_err());
+
+    pool.check();
+    assert!(pool.primary().get_slot().is_ok());
+    let metrics = pool.metrics();
+    assert!(metrics.contains("rpc_endpoint_primary{endpoint=\"1\",host=\"succeeds\"} 1"));
+    assert!(metrics.contains("rpc_endpoint_score{endpoint=\"0\",host=\"fails\"} 0.0"));
+}
+
+#[test]
+fn labels_leave_out_paths_and_keys() {
+    assert_eq!(
+        endpoint_label("https://mainnet.helius-rpc.com/?api-key=secret"),
+        "mainnet.helius-rpc.com"
+    );
+    assert_eq!(endpoint_label("http://127.0.0.1:8899"), "127.0.0.1:8899");
+}
@@ -0,0 +1,153 @@
+//! Send rate limits, send error classification and how a sending endpoint reacts to each kind.
+
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+use solana_onchain_arbitrage_bot::blockhash::BlockhashService;
+use solana_onchain_arbitrage_bot::rpc::{SendError, TxSender};
+use solana_onchain_arbitrage_bot::rpc_pool::RpcPool;
+use solana_onchain_arbitrage_bot::sender::{SendingEndpoint, TokenBucket};
+use solana_sdk::hash::Hash;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{Transaction, TransactionError, VersionedTransaction};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Fails every send with the error `error` builds, counting the attempts.
+struct FailingSender {
+    error: fn() -> SendError,
+    attempts: AtomicUsize,
+}
+
+impl TxSender for FailingSender {
+    fn send_transaction(
+        &self,
+        _tx: &VersionedTransaction,
+        _max_retries: u64,
+    ) -> Result<Signature, SendError> {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        Err((self.error)())
+    }
+}
+
+fn blockhash_service() -> Arc<BlockhashService> {
+    let client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+    let pool = RpcPool::with_clients(vec![("mock".to_string(), client)], 25);
+    Arc::new(BlockhashService::new(Arc::new(pool), vec![], None))
+}
+
+fn transaction(blockhash: Hash) -> VersionedTransaction {
+    let message = Message::new_with_blockhash(&[], Some(&Pubkey::new_unique()), &blockhash);
+    Transaction::new_unsigned(message).into()
+}
+
+fn response_error(code: i64, message: &str) -> SendError {
+    ClientError::from(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        code,
+        message: message.to_string(),
+        data: RpcResponseErrorData::Empty,
+    }))
+    .into()
+}
+
+#[test]
+fn token_bucket_allows_bursts_then_the_rate() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(10.0, 2, start);
+    assert!(bucket.try_take(start));
+    assert!(bucket.try_take(start));
+    assert!(!bucket.try_take(start));
+
+    assert!(!bucket.try_take(start + Duration::from_millis(50)));
+    assert!(bucket.try_take(start + Duration::from_millis(100)));
+    // A long pause refills no more than the burst
+    let later = start + Duration::from_secs(10);
+    assert!(bucket.try_take(later));
+    assert!(bucket.try_take(later));
+    assert!(!bucket.try_take(later));
+}
+
+#[test]
+fn classifies_rpc_errors() {
+    assert!(matches!(
+        response_error(-32005, "Node is behind by 42 slots"),
+        SendError::NodeUnhealthy(_)
+    ));
+    assert!(matches!(
+        response_error(429, "Too many requests for a specific RPC call"),
+        SendError::RateLimited
+    ));
+    assert!(matches!(
+        response_error(-32002, "Transaction simulation failed: Blockhash not found"),
+        SendError::BlockhashNotFound
+    ));
+    assert!(matches!(
+        SendError::from(ClientError::from(ClientErrorKind::TransactionError(
+            TransactionError::BlockhashNotFound
+        ))),
+        SendError::BlockhashNotFound
+    ));
+    assert!(matches!(
+        SendError::from(ClientError::from(ClientErrorKind::Io(
+            std::io::ErrorKind::ConnectionRefused.into()
+        ))),
+        SendError::Connection(_)
+    ));
+    assert!(matches!(
+        response_error(
+            -32002,
+            "Transaction simulation failed: Error processing Instruction 2"
+        ),
+        SendError::Other(_)
+    ));
+}
+
+#[test]
+fn rate_limited_and_unhealthy_endpoints_are_paused() {
+    for error in [
+        (|| SendError::RateLimited) as fn() -> SendError,
+        || SendError::NodeUnhealthy("behind".to_string()),
+        || SendError::Connection("refused".to_string()),
+    ] {
+        let inner = Arc::new(FailingSender {
+            error,
+            attempts: AtomicUsize::new(0),
+        });
+        let endpoint =
+            SendingEndpoint::new("mock".to_string(), inner.clone(), blockhash_service(), None);
+        let tx = transaction(Hash::new_unique());
+
+        assert!(!endpoint.send_transaction(&tx, 0).unwrap_err().is_skipped());
+        let second = endpoint.send_transaction(&tx, 0).unwrap_err();
+        assert!(matches!(second, SendError::Paused(_)));
+        assert_eq!(inner.attempts.load(Ordering::Relaxed), 1);
+    }
+}
+
+#[test]
+fn unknown_blockhash_is_refetched() {
+    let service = blockhash_service();
+    let blockhash = service.refresh().unwrap().hash;
+    let endpoint = SendingEndpoint::new(
+        "mock".to_string(),
+        Arc::new(FailingSender {
+            error: || SendError::BlockhashNotFound,
+            attempts: AtomicUsize::new(0),
+        }),
+        service.clone(),
+        None,
+    );
+
+    // A transaction with an older blockhash leaves the current one alone
+    let _ = endpoint.send_transaction(&transaction(Hash::new_unique()), 0);
+    assert!(service.current().is_some());
+
+    let _ = endpoint.send_transaction(&transaction(blockhash), 0);
+    assert!(service.current().is_none());
+    assert!(service.fresh().is_err());
+    service.poll().unwrap();
+    assert_eq!(service.fresh().unwrap(), blockhash);
+}
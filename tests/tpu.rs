@@ -0,0 +1,40 @@
+//! Picking the next leaders to send to from a slice of the leader schedule.
+
+use solana_onchain_arbitrage_bot::tpu::{tpu_quic_address, upcoming_leaders};
+use solana_sdk::pubkey::Pubkey;
+use std::net::SocketAddr;
+
+/// A schedule where each leader holds four consecutive slots.
+fn schedule(leaders: &[Pubkey]) -> Vec<Pubkey> {
+    leaders.iter().flat_map(|l| [*l; 4]).collect()
+}
+
+#[test]
+fn skips_past_slots_and_repeated_leaders() {
+    let (a, b, c) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let schedule = schedule(&[a, b, a, c]);
+
+    assert_eq!(upcoming_leaders(&schedule, 100, 100, 2), vec![a, b]);
+    // Slot 106 is the third slot of b
+    assert_eq!(upcoming_leaders(&schedule, 100, 106, 2), vec![b, a]);
+    assert_eq!(upcoming_leaders(&schedule, 100, 106, 3), vec![b, a, c]);
+    assert_eq!(upcoming_leaders(&schedule, 100, 115, 2), vec![c]);
+    assert!(upcoming_leaders(&schedule, 100, 116, 2).is_empty());
+}
+
+#[test]
+fn quic_address_falls_back_to_the_udp_port() {
+    let quic: SocketAddr = "10.0.0.1:8009".parse().unwrap();
+    let udp: SocketAddr = "10.0.0.1:8003".parse().unwrap();
+    assert_eq!(tpu_quic_address(Some(quic), Some(udp)), Some(quic));
+    assert_eq!(tpu_quic_address(None, Some(udp)), Some(quic));
+    assert_eq!(tpu_quic_address(None, None), None);
+
+    // No port is left past the top one, so the node is skipped
+    let top: SocketAddr = "10.0.0.1:65533".parse().unwrap();
+    assert_eq!(tpu_quic_address(None, Some(top)), None);
+}
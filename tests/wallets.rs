@@ -0,0 +1,63 @@
+//! Handing out wallets per send and per mint.
+
+use solana_onchain_arbitrage_bot::config::WalletRotation;
+use solana_onchain_arbitrage_bot::wallets::{WalletSigner, Wallets};
+use solana_sdk::signature::Keypair;
+use std::sync::Arc;
+
+fn wallets(count: usize, rotation: WalletRotation) -> Wallets {
+    let keypairs = (0..count)
+        .map(|_| Arc::new(Keypair::new()) as Arc<WalletSigner>)
+        .collect();
+    Wallets::new(keypairs, rotation).unwrap()
+}
+
+#[test]
+fn per_send_rotation_takes_each_wallet_in_turn() {
+    let wallets = wallets(3, WalletRotation::PerSend);
+    let pubkeys = wallets.pubkeys();
+    let sent: Vec<_> = (0..4).map(|_| wallets.for_send("mint").pubkey()).collect();
+    assert_eq!(sent, vec![pubkeys[0], pubkeys[1], pubkeys[2], pubkeys[0]]);
+}
+
+#[test]
+fn per_mint_rotation_keeps_one_wallet_per_mint() {
+    let wallets = wallets(2, WalletRotation::PerMint);
+    let a = wallets.for_send("a").pubkey();
+    let b = wallets.for_send("b").pubkey();
+    assert_ne!(a, b);
+    assert_eq!(wallets.for_send("a").pubkey(), a);
+    assert_eq!(wallets.for_send("b").pubkey(), b);
+    assert_eq!(wallets.primary().pubkey(), wallets.pubkeys()[0]);
+}
+
+#[test]
+fn at_least_one_wallet_is_required() {
+    assert!(Wallets::new(vec![], WalletRotation::PerSend).is_err());
+}
+
+#[test]
+fn every_wallet_pays_with_its_own_fee_payer() {
+    let fee_payers: Vec<Arc<WalletSigner>> = (0..2)
+        .map(|_| Arc::new(Keypair::new()) as Arc<WalletSigner>)
+        .collect();
+    let paid = wallets(2, WalletRotation::PerSend)
+        .with_fee_payers(fee_payers.clone())
+        .unwrap();
+    for fee_payer in &fee_payers {
+        let wallet = paid.for_send("mint");
+        assert_eq!(paid.fee_payer_for(&wallet).pubkey(), fee_payer.pubkey());
+    }
+
+    // A fee payer shared by both wallets is refused
+    let shared = fee_payers[0].clone();
+    assert!(wallets(2, WalletRotation::PerSend)
+        .with_fee_payers(vec![shared.clone()])
+        .is_err());
+    assert!(wallets(2, WalletRotation::PerSend)
+        .with_fee_payers(vec![shared.clone(), shared])
+        .is_err());
+    let own = wallets(1, WalletRotation::PerSend);
+    let wallet = own.primary().clone();
+    assert_eq!(own.fee_payer_for(&wallet).pubkey(), wallet.pubkey());
+}